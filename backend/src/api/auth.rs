@@ -0,0 +1,356 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse},
+    error::ResponseError,
+    get,
+    http::{
+        header::{ContentType, AUTHORIZATION, WWW_AUTHENTICATE},
+        StatusCode,
+    },
+    post,
+    web::{Data, Json},
+    HttpRequest, HttpResponse,
+};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use common::{
+    api::routes,
+    model::auth::{LoginRequest, LoginResponse, SessionInfo},
+};
+use derive_more::Display;
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::{BufRead, Error, ErrorKind},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+#[derive(Debug, Display)]
+pub enum AuthError {
+    InvalidCredentials,
+    Unauthorized,
+}
+
+impl ResponseError for AuthError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .insert_header((WWW_AUTHENTICATE, "Bearer"))
+            .body(self.to_string())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AuthError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            AuthError::Unauthorized => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+struct Session {
+    username: String,
+    expires_at: Instant,
+}
+
+/// Accounts allowed to sign in and the sessions issued to them. Sessions live
+/// in memory, so they are per replica and end when the backend restarts.
+pub struct SessionStore {
+    ttl: Duration,
+    /// Username to argon2 PHC hash. Empty when authentication is disabled.
+    users: HashMap<String, String>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionStore {
+    pub fn new(users: HashMap<String, String>, ttl: Duration) -> SessionStore {
+        SessionStore {
+            ttl,
+            users,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reads `username:hash` lines from `KITODAR_USERS_FILE`. Without it the
+    /// API stays open, as before authentication existed.
+    pub fn from_env() -> std::io::Result<SessionStore> {
+        let ttl = std::env::var("KITODAR_SESSION_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse::<u64>().ok())
+            .unwrap_or(8 * 60 * 60);
+
+        let users = match std::env::var("KITODAR_USERS_FILE") {
+            Ok(path) => parse_users(&std::fs::read_to_string(path)?)?,
+            Err(_) => {
+                tracing::warn!("KITODAR_USERS_FILE is not set, the API is unauthenticated");
+                HashMap::new()
+            }
+        };
+        Ok(SessionStore::new(users, Duration::from_secs(ttl)))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.users.is_empty()
+    }
+
+    fn verify(&self, username: &str, password: &str) -> bool {
+        self.users
+            .get(username)
+            .and_then(|hash| PasswordHash::new(hash).ok())
+            .map(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    }
+
+    /// Issues a new session token when the credentials are valid.
+    pub fn login(&self, username: &str, password: &str) -> Option<String> {
+        if !self.verify(username, password) {
+            return None;
+        }
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        self.sessions.lock().unwrap().insert(
+            token.clone(),
+            Session {
+                username: username.to_owned(),
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+        Some(token)
+    }
+
+    /// Returns the user and remaining lifetime of a live session.
+    pub fn lookup(&self, token: &str) -> Option<(String, Duration)> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Instant::now();
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions
+            .get(token)
+            .map(|session| (session.username.clone(), session.expires_at - now))
+    }
+
+    pub fn logout(&self, token: &str) {
+        self.sessions.lock().unwrap().remove(token);
+    }
+}
+
+fn parse_users(contents: &str) -> std::io::Result<HashMap<String, String>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once(':') {
+            Some((username, hash)) if PasswordHash::new(hash).is_ok() => {
+                Ok((username.to_owned(), hash.to_owned()))
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("malformed entry in users file: {line}"),
+            )),
+        })
+        .collect()
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Routes needed to sign in, and the documentation, which stay open.
+fn is_exempt(path: &str) -> bool {
+    [
+        routes::LOGIN,
+        routes::LOGOUT,
+        routes::SESSION,
+        routes::OPENAPI,
+        routes::DOCS,
+        routes::DOCS_SCRIPT,
+        routes::DOCS_STYLE,
+    ]
+    .contains(&path)
+}
+
+/// Rejects API requests without a live session, except those needed to sign
+/// in and the documentation.
+pub fn require_session<S>(
+    store: &SessionStore,
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    let allowed = !store.is_enabled()
+        || is_exempt(req.path())
+        || bearer_token(req.request())
+            .and_then(|token| store.lookup(token))
+            .is_some();
+
+    let call = if allowed { Ok(srv.call(req)) } else { Err(req) };
+    async move {
+        match call {
+            Ok(call) => call.await,
+            Err(req) => Ok(req.into_response(AuthError::Unauthorized.error_response())),
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = "/api",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = LoginResponse),
+        (status = 401, description = "Unknown user or wrong password"),
+    )
+)]
+#[post("/login")]
+pub async fn login(
+    store: Data<SessionStore>,
+    request: Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, AuthError> {
+    match store.login(&request.username, &request.password) {
+        Some(token) => {
+            tracing::info!(username = %request.username, "signed in");
+            Ok(Json(LoginResponse {
+                token,
+                username: request.username.clone(),
+                expires_in: store.ttl.as_secs(),
+            }))
+        }
+        None => {
+            tracing::warn!(username = %request.username, "failed sign-in");
+            Err(AuthError::InvalidCredentials)
+        }
+    }
+}
+
+#[utoipa::path(
+    context_path = "/api",
+    responses((status = 204, description = "Session ended")),
+)]
+#[post("/logout")]
+pub async fn logout(store: Data<SessionStore>, req: HttpRequest) -> HttpResponse {
+    if let Some(token) = bearer_token(&req) {
+        store.logout(token);
+    }
+    HttpResponse::NoContent().finish()
+}
+
+#[utoipa::path(
+    context_path = "/api",
+    responses((status = 200, description = "The caller's session, if any", body = SessionInfo)),
+)]
+#[get("/session")]
+pub async fn get_session(store: Data<SessionStore>, req: HttpRequest) -> Json<SessionInfo> {
+    let session = bearer_token(&req).and_then(|token| store.lookup(token));
+    Json(SessionInfo {
+        auth_enabled: store.is_enabled(),
+        username: session.as_ref().map(|(username, _)| username.clone()),
+        expires_in: session.map(|(_, remaining)| remaining.as_secs()),
+    })
+}
+
+/// `backend hash-password`: reads a password from stdin and prints the hash
+/// to put in the users file.
+pub fn hash_password_command() -> std::io::Result<()> {
+    let mut password = String::new();
+    std::io::stdin().lock().read_line(&mut password)?;
+    let password = password.trim_end_matches(['\r', '\n']);
+
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())
+        .map_err(|e| Error::other(e.to_string()))?;
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| Error::other(e.to_string()))?;
+    println!("{hash}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_exempt, parse_users, SessionStore};
+    use argon2::{
+        password_hash::{PasswordHasher, SaltString},
+        Argon2,
+    };
+    use common::api::routes;
+    use std::{collections::HashMap, time::Duration};
+
+    fn hash(password: &str) -> String {
+        let salt = SaltString::encode_b64(b"kitodar-test-salt").unwrap();
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .unwrap()
+            .to_string()
+    }
+
+    fn store(ttl: Duration) -> SessionStore {
+        SessionStore::new(HashMap::from([("alice".to_owned(), hash("secret"))]), ttl)
+    }
+
+    #[test]
+    fn users_file_skips_comments_and_blank_lines() {
+        let contents = format!("# operators\n\nalice:{}\n", hash("secret"));
+        let users = parse_users(&contents).unwrap();
+        assert_eq!(users.len(), 1);
+        assert!(users.contains_key("alice"));
+    }
+
+    #[test]
+    fn users_file_rejects_malformed_lines() {
+        assert!(parse_users("alice").is_err());
+        assert!(parse_users("alice:not-a-hash").is_err());
+        assert!(parse_users(&format!("alice:{}\nbob", hash("secret"))).is_err());
+    }
+
+    #[test]
+    fn sessions_are_found_until_they_expire() {
+        let live = store(Duration::from_secs(60));
+        let token = live.login("alice", "secret").unwrap();
+        assert_eq!(
+            live.lookup(&token).map(|(username, _)| username),
+            Some("alice".to_owned())
+        );
+
+        let expired = store(Duration::ZERO);
+        let token = expired.login("alice", "secret").unwrap();
+        assert!(expired.lookup(&token).is_none());
+    }
+
+    #[test]
+    fn wrong_passwords_get_no_session() {
+        assert!(store(Duration::from_secs(60))
+            .login("alice", "guess")
+            .is_none());
+    }
+
+    #[test]
+    fn only_sign_in_and_docs_are_exempt() {
+        for path in [
+            routes::LOGIN,
+            routes::LOGOUT,
+            routes::SESSION,
+            routes::OPENAPI,
+            routes::DOCS,
+            routes::DOCS_SCRIPT,
+            routes::DOCS_STYLE,
+        ] {
+            assert!(is_exempt(path), "{path}");
+        }
+        for path in [
+            routes::USERS,
+            routes::USERS_V2,
+            routes::STATS,
+            routes::LOG_LEVEL,
+        ] {
+            assert!(!is_exempt(path), "{path}");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::repository::kub::KubeRepository;
+use actix_web::{get, web::Data, HttpResponse};
+use serde::Serialize;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// API group, resource and verb of every request the API handlers make.
+const REQUIRED_PERMISSIONS: [(&str, &str, &str); 11] = [
+    ("", "namespaces", "create"),
+    ("", "namespaces", "delete"),
+    ("", "namespaces", "get"),
+    ("", "namespaces", "list"),
+    ("", "namespaces", "patch"),
+    ("", "events", "list"),
+    ("", "persistentvolumeclaims", "list"),
+    ("", "pods", "list"),
+    ("", "services", "list"),
+    ("apps", "deployments", "list"),
+    ("apps", "statefulsets", "list"),
+];
+
+/// How long granted permissions are trusted before they are reviewed again.
+/// Denied ones are reviewed on every probe, so a fixed role is noticed.
+const PERMISSION_CACHE_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Serialize, Clone)]
+struct PermissionCheck {
+    group: &'static str,
+    resource: &'static str,
+    verb: &'static str,
+    allowed: bool,
+    error: Option<String>,
+}
+
+/// Permission reviews from the last probe that found them all granted.
+#[derive(Default)]
+pub struct PermissionCache(Mutex<Option<(Instant, Vec<PermissionCheck>)>>);
+
+impl PermissionCache {
+    fn get(&self) -> Option<Vec<PermissionCheck>> {
+        match &*self.0.lock().unwrap() {
+            Some((reviewed_at, permissions)) if reviewed_at.elapsed() < PERMISSION_CACHE_TTL => {
+                Some(permissions.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn set(&self, permissions: &[PermissionCheck]) {
+        *self.0.lock().unwrap() = permissions
+            .iter()
+            .all(|p| p.allowed)
+            .then(|| (Instant::now(), permissions.to_vec()));
+    }
+}
+
+async fn review_permissions(kube_repo: &KubeRepository) -> Vec<PermissionCheck> {
+    let mut permissions = Vec::new();
+    for (group, resource, verb) in REQUIRED_PERMISSIONS {
+        let (allowed, error) = match kube_repo.can_i(group, resource, verb).await {
+            Ok(allowed) => (allowed, None),
+            Err(e) => (false, Some(format!("{e:?}"))),
+        };
+        permissions.push(PermissionCheck {
+            group,
+            resource,
+            verb,
+            allowed,
+            error,
+        });
+    }
+    permissions
+}
+
+#[derive(Serialize)]
+struct ReadinessReport {
+    ready: bool,
+    api_server_version: Option<String>,
+    api_server_error: Option<String>,
+    permissions: Vec<PermissionCheck>,
+}
+
+#[get("/healthz")]
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+#[get("/readyz")]
+pub async fn readyz(
+    kube_repo: Data<KubeRepository>,
+    permission_cache: Data<PermissionCache>,
+) -> HttpResponse {
+    let (api_server_version, api_server_error) = match kube_repo.api_server_version().await {
+        Ok(version) => (Some(version), None),
+        Err(e) => (None, Some(format!("{e:?}"))),
+    };
+
+    let permissions = match permission_cache.get() {
+        _ if api_server_error.is_some() => Vec::new(),
+        Some(permissions) => permissions,
+        None => {
+            let permissions = review_permissions(&kube_repo).await;
+            permission_cache.set(&permissions);
+            permissions
+        }
+    };
+
+    let ready = api_server_error.is_none() && permissions.iter().all(|p| p.allowed);
+    let report = ReadinessReport {
+        ready,
+        api_server_version,
+        api_server_error,
+        permissions,
+    };
+    if ready {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::ServiceUnavailable().json(report)
+    }
+}
@@ -0,0 +1,232 @@
+use common::model::user_v2::UserV2;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// How long a key stays reserved by a request that never completed or
+/// aborted, for instance because the client disconnected and the handler was
+/// dropped. Far longer than creating a namespace takes.
+const IN_FLIGHT_TIMEOUT: Duration = Duration::from_secs(60);
+
+enum Entry {
+    /// `generation` tells the reservation holding the key apart from one
+    /// that outlived the timeout, whose key may have been taken again since.
+    InFlight {
+        fingerprint: String,
+        generation: u64,
+        expires_at: Instant,
+    },
+    Completed {
+        fingerprint: String,
+        user: Box<UserV2>,
+        expires_at: Instant,
+    },
+}
+
+pub enum Lookup<'a> {
+    Fresh(Reservation<'a>),
+    Replay(Box<UserV2>),
+    InFlight,
+    Mismatch,
+}
+
+pub struct IdempotencyStore {
+    ttl: Duration,
+    in_flight_timeout: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+    next_generation: AtomicU64,
+}
+
+impl IdempotencyStore {
+    pub fn new(ttl: Duration) -> IdempotencyStore {
+        IdempotencyStore {
+            ttl,
+            in_flight_timeout: IN_FLIGHT_TIMEOUT,
+            entries: Mutex::new(HashMap::new()),
+            next_generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn from_env() -> IdempotencyStore {
+        let ttl = std::env::var("KITODAR_IDEMPOTENCY_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse::<u64>().ok())
+            .unwrap_or(24 * 60 * 60);
+        IdempotencyStore::new(Duration::from_secs(ttl))
+    }
+
+    /// Reserves `key` for a request identified by `fingerprint`, or reports
+    /// what an earlier request with the same key already did. With
+    /// `Lookup::Fresh` the caller holds the key through a [`Reservation`].
+    pub fn begin(&self, key: &str, fingerprint: &str) -> Lookup<'_> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, entry| match entry {
+            Entry::InFlight { expires_at, .. } => *expires_at > now,
+            Entry::Completed { expires_at, .. } => *expires_at > now,
+        });
+
+        match entries.get(key) {
+            Some(Entry::InFlight { fingerprint: f, .. }) if f == fingerprint => Lookup::InFlight,
+            Some(Entry::Completed {
+                fingerprint: f,
+                user,
+                ..
+            }) if f == fingerprint => Lookup::Replay(user.clone()),
+            Some(_) => Lookup::Mismatch,
+            None => {
+                let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+                entries.insert(
+                    key.to_owned(),
+                    Entry::InFlight {
+                        fingerprint: fingerprint.to_owned(),
+                        generation,
+                        expires_at: now + self.in_flight_timeout,
+                    },
+                );
+                Lookup::Fresh(Reservation {
+                    store: self,
+                    key: key.to_owned(),
+                    fingerprint: fingerprint.to_owned(),
+                    generation,
+                    completed: false,
+                })
+            }
+        }
+    }
+
+    fn complete(&self, key: &str, fingerprint: &str, user: UserV2) {
+        self.entries.lock().unwrap().insert(
+            key.to_owned(),
+            Entry::Completed {
+                fingerprint: fingerprint.to_owned(),
+                user: Box::new(user),
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+    }
+
+    /// Releases `key` after a failed attempt so the client may retry it,
+    /// unless it expired and a later request reserved it in the meantime.
+    fn abort(&self, key: &str, generation: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if matches!(
+            entries.get(key),
+            Some(Entry::InFlight { generation: g, .. }) if *g == generation
+        ) {
+            entries.remove(key);
+        }
+    }
+}
+
+/// An idempotency key held by a running request. Dropping it without
+/// `complete`, on failure or when the handler is cancelled by a disconnect,
+/// releases the key so a retry is not answered with 409.
+pub struct Reservation<'a> {
+    store: &'a IdempotencyStore,
+    key: String,
+    fingerprint: String,
+    generation: u64,
+    completed: bool,
+}
+
+impl Reservation<'_> {
+    pub fn complete(mut self, user: UserV2) {
+        self.store.complete(&self.key, &self.fingerprint, user);
+        self.completed = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.store.abort(&self.key, self.generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdempotencyStore, Lookup};
+    use common::model::{
+        user::{UserMetadata, VmsVersion},
+        user_v2::{UserStatus, UserV2},
+    };
+    use std::time::Duration;
+
+    fn user() -> UserV2 {
+        UserV2 {
+            user_name: "alice".to_owned(),
+            vms_version: VmsVersion::XProtect2024R1,
+            server_type: "standard".to_owned(),
+            status: UserStatus::Active,
+            created_at: None,
+            updated_at: None,
+            owner: None,
+            metadata: UserMetadata::default(),
+            uid: None,
+        }
+    }
+
+    #[test]
+    fn completed_requests_are_replayed() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        match store.begin("key", "alice") {
+            Lookup::Fresh(reservation) => reservation.complete(user()),
+            _ => panic!("first use of a key must be fresh"),
+        }
+        assert!(
+            matches!(store.begin("key", "alice"), Lookup::Replay(replayed) if *replayed == user())
+        );
+    }
+
+    #[test]
+    fn keys_cannot_be_reused_for_another_request() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let Lookup::Fresh(reservation) = store.begin("key", "alice") else {
+            panic!("first use of a key must be fresh");
+        };
+        assert!(matches!(store.begin("key", "alice"), Lookup::InFlight));
+        assert!(matches!(store.begin("key", "bob"), Lookup::Mismatch));
+        reservation.complete(user());
+        assert!(matches!(store.begin("key", "bob"), Lookup::Mismatch));
+    }
+
+    #[test]
+    fn failed_requests_release_their_key() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        drop(store.begin("key", "alice"));
+        assert!(matches!(store.begin("key", "alice"), Lookup::Fresh(_)));
+    }
+
+    #[test]
+    fn completed_keys_expire() {
+        let store = IdempotencyStore::new(Duration::ZERO);
+        if let Lookup::Fresh(reservation) = store.begin("key", "alice") {
+            reservation.complete(user());
+        }
+        assert!(matches!(store.begin("key", "bob"), Lookup::Fresh(_)));
+    }
+
+    #[test]
+    fn stale_reservations_do_not_release_a_newer_one() {
+        let store = IdempotencyStore {
+            in_flight_timeout: Duration::ZERO,
+            ..IdempotencyStore::new(Duration::from_secs(60))
+        };
+        let stale = store.begin("key", "alice");
+        let current = store.begin("key", "alice");
+        assert!(matches!(current, Lookup::Fresh(_)));
+        drop(stale);
+        assert!(store.entries.lock().unwrap().contains_key("key"));
+        drop(current);
+        assert!(!store.entries.lock().unwrap().contains_key("key"));
+    }
+}
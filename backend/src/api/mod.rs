@@ -1 +1,8 @@
+pub mod auth;
+pub mod health;
+pub mod idempotency;
+pub mod openapi;
+pub mod stats;
+pub mod sync;
 pub mod user;
+pub mod user_v2;
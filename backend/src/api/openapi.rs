@@ -0,0 +1,127 @@
+use crate::api::{auth, stats, sync, user, user_v2};
+use actix_web::{get, http::header::ContentType, HttpResponse};
+use common::model::{
+    auth::{LoginRequest, LoginResponse, SessionInfo},
+    details::{NamespaceEvent, NamespaceResource, UserDetails},
+    query::{SortField, SortOrder},
+    stats::{FleetStats, Operation},
+    sync::{DesiredUser, SyncDocument, SyncPlan, SyncResult},
+    user::{
+        ApplyOutcome, ApplyUserRequest, ApplyUserResponse, CreateUserRequest, User, UserMetadata,
+        VmsVersion,
+    },
+    user_v2::{ApplyUserResponseV2, UserStatus, UserV2},
+};
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "kitodar", description = "Kubernetes tenant management API"),
+    paths(
+        user::get_users,
+        user::get_user,
+        user::create_user,
+        user::apply_user,
+        user::upgrade_user,
+        user::update_user_metadata,
+        user::delete_user,
+        sync::post_sync,
+        stats::get_stats,
+        auth::login,
+        auth::logout,
+        auth::get_session,
+        user_v2::get_users_v2,
+        user_v2::get_user_v2,
+        user_v2::get_user_details_v2,
+        user_v2::create_user_v2,
+        user_v2::apply_user_v2,
+        user_v2::upgrade_user_v2,
+        user_v2::update_user_metadata_v2,
+        user_v2::delete_user_v2,
+    ),
+    components(schemas(
+        User,
+        UserMetadata,
+        VmsVersion,
+        CreateUserRequest,
+        ApplyUserRequest,
+        ApplyUserResponse,
+        ApplyOutcome,
+        DesiredUser,
+        SyncDocument,
+        SyncPlan,
+        SyncResult,
+        UserV2,
+        UserStatus,
+        ApplyUserResponseV2,
+        UserDetails,
+        NamespaceResource,
+        NamespaceEvent,
+        SortField,
+        SortOrder,
+        FleetStats,
+        Operation,
+        LoginRequest,
+        LoginResponse,
+        SessionInfo,
+    ))
+)]
+pub struct ApiDoc;
+
+#[get("/openapi.json")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+#[get("/docs")]
+pub async fn api_docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(ContentType::html())
+        .body(include_str!("docs.html"))
+}
+
+/// The Swagger UI assets are compiled in so the docs work without internet
+/// access.
+#[get("/docs/swagger-ui-bundle.js")]
+pub async fn api_docs_script() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/javascript; charset=utf-8")
+        .body(include_str!("swagger-ui/swagger-ui-bundle.js"))
+}
+
+#[get("/docs/swagger-ui.css")]
+pub async fn api_docs_style() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/css; charset=utf-8")
+        .body(include_str!("swagger-ui/swagger-ui.css"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiDoc;
+    use utoipa::OpenApi;
+
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    /// Fails when the handlers or model types change without regenerating the
+    /// committed spec. Run with `UPDATE_OPENAPI=1` to rewrite it.
+    #[test]
+    fn openapi_spec_is_up_to_date() {
+        let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(SPEC_PATH, &generated).unwrap();
+        }
+        let committed = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "openapi.json is out of date, rerun the tests with UPDATE_OPENAPI=1"
+        );
+    }
+
+    /// The docs page must keep working in clusters without internet access.
+    #[test]
+    fn docs_page_loads_no_remote_assets() {
+        let page = include_str!("docs.html");
+        assert!(!page.contains("http://") && !page.contains("https://"));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::api::user::{cluster_error, UserError};
+use crate::operations::OperationLog;
+use crate::repository::kub::KubeRepository;
+use actix_web::{get, web::Data, web::Json};
+use common::model::{stats::FleetStats, user::VmsVersion, user_v2::UserStatus};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Releases supported by default, counting back from the latest one.
+const SUPPORTED_RELEASES: usize = 3;
+
+/// Oldest supported version, from `KITODAR_MIN_SUPPORTED_VERSION` or the
+/// last `SUPPORTED_RELEASES` releases.
+fn min_supported_version() -> VmsVersion {
+    std::env::var("KITODAR_MIN_SUPPORTED_VERSION")
+        .ok()
+        .and_then(|version| VmsVersion::from_str(&version).ok())
+        .unwrap_or_else(|| {
+            let versions = VmsVersion::all();
+            versions[versions.len().saturating_sub(SUPPORTED_RELEASES)]
+        })
+}
+
+#[utoipa::path(
+    context_path = "/api",
+    responses(
+        (status = 200, description = "Tenant counts, unhealthy tenants and recent operations", body = FleetStats),
+        (status = 502, description = "Tenants or their workloads could not be listed"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[get("/stats")]
+pub async fn get_stats(
+    kube_repo: Data<KubeRepository>,
+    operations: Data<OperationLog>,
+) -> Result<Json<FleetStats>, UserError> {
+    let users = kube_repo.get_users().await.map_err(cluster_error)?;
+    let namespaces: Vec<String> = users.iter().map(|user| user.user_name.clone()).collect();
+    let unready_namespaces = kube_repo
+        .get_unready_namespaces(&namespaces)
+        .await
+        .map_err(cluster_error)?;
+    let min_supported_version = min_supported_version();
+
+    let mut by_vms_version: BTreeMap<String, usize> = VmsVersion::all()
+        .into_iter()
+        .map(|version| (version.to_string(), 0))
+        .collect();
+    let mut by_server_type = BTreeMap::new();
+    let mut unsupported = Vec::new();
+    let mut not_ready = Vec::new();
+    for user in &users {
+        *by_vms_version
+            .entry(user.vms_version.to_string())
+            .or_default() += 1;
+        *by_server_type.entry(user.server_type.clone()).or_default() += 1;
+        if user.vms_version < min_supported_version {
+            unsupported.push(user.user_name.clone());
+        }
+        if user.status != UserStatus::Active || unready_namespaces.contains(&user.user_name) {
+            not_ready.push(user.user_name.clone());
+        }
+    }
+
+    Ok(Json(FleetStats {
+        total: users.len(),
+        by_vms_version,
+        by_server_type,
+        min_supported_version,
+        unsupported,
+        not_ready,
+        recent_operations: operations.recent(),
+    }))
+}
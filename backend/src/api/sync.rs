@@ -0,0 +1,86 @@
+use crate::repository::kub::{KubeError, KubeRepository};
+use crate::sync::sync_users;
+use actix_web::{
+    error::ResponseError,
+    http::{header::ContentType, StatusCode},
+    post,
+    web::{Bytes, Data, Query},
+    HttpResponse,
+};
+use common::model::sync::SyncDocument;
+use derive_more::Display;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct SyncQuery {
+    /// Only compute the plan.
+    #[serde(default)]
+    dry_run: bool,
+    /// Never delete tenants missing from the document.
+    #[serde(default)]
+    no_delete: bool,
+}
+
+#[derive(Debug, Display)]
+pub enum SyncError {
+    BadSyncDocument,
+    ClusterUnavailable,
+    ClusterFailure,
+}
+
+impl ResponseError for SyncError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::json())
+            .body(self.to_string())
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SyncError::BadSyncDocument => StatusCode::BAD_REQUEST,
+            SyncError::ClusterUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            SyncError::ClusterFailure => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+/// Accepts the desired tenant list as YAML or JSON (JSON being valid YAML).
+/// Answers 424 with the full result when some planned operations failed.
+/// Downgrades are only reported, in `plan.downgrade`.
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(SyncQuery),
+    request_body(content = SyncDocument, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Plan computed or applied", body = SyncResult),
+        (status = 400, description = "Malformed sync document"),
+        (status = 424, description = "Some planned operations failed", body = SyncResult),
+        (status = 502, description = "Tenants could not be listed, nothing was applied"),
+        (status = 503, description = "Cluster unreachable, nothing was applied"),
+    )
+)]
+#[post("/sync")]
+pub async fn post_sync(
+    kube_repo: Data<KubeRepository>,
+    query: Query<SyncQuery>,
+    body: Bytes,
+) -> Result<HttpResponse, SyncError> {
+    let document: SyncDocument =
+        serde_yaml::from_slice(&body).map_err(|_| SyncError::BadSyncDocument)?;
+
+    let result = sync_users(&kube_repo, &document, query.dry_run, !query.no_delete)
+        .await
+        .map_err(|error| match error {
+            KubeError::Unavailable => SyncError::ClusterUnavailable,
+            _ => SyncError::ClusterFailure,
+        })?;
+    let status = if result.failed.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::FAILED_DEPENDENCY
+    };
+    Ok(HttpResponse::build(status).json(result))
+}
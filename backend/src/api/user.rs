@@ -1,25 +1,57 @@
-use crate::repository::kub::KubeRepository;
+use crate::api::idempotency::{IdempotencyStore, Lookup, IDEMPOTENCY_KEY_HEADER};
+use crate::repository::kub::{KubeError, KubeRepository};
 use actix_web::{
     delete,
+    dev::{Service, ServiceRequest, ServiceResponse},
     error::ResponseError,
     get,
-    http::{header::ContentType, StatusCode},
-    patch, post,
+    http::{
+        header::{
+            ContentType, ETag, EntityTag, Header, HeaderName, HeaderValue, IfMatch, IF_MATCH, LINK,
+        },
+        StatusCode,
+    },
+    patch, post, put,
     web::Data,
     web::Json,
     web::Path,
-    HttpResponse,
+    web::Query,
+    HttpRequest, HttpResponse,
+};
+use common::api::routes;
+use common::model::{
+    query::{SortField, SortOrder, UserQuery},
+    user::{
+        ApplyOutcome, ApplyUserRequest, ApplyUserResponse, CreateUserRequest, User, UserMetadata,
+        VmsVersion,
+    },
+    user_v2::UserV2,
 };
-use common::model::user::User;
-use common::model::user::VmsVersion;
 use derive_more::Display;
+use k8s_openapi::chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::future::Future;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct DryRunQuery {
+    /// Validate the change against the API server without persisting it.
+    #[serde(default)]
+    pub dry_run: bool,
+}
 
-#[derive(Deserialize)]
-pub struct CreateUserRequest {
-    user_name: String,
-    vms_version: String,
+#[derive(Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UpgradeQuery {
+    /// Validate the change against the API server without persisting it.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Release to upgrade to, the latest one by default. It must be newer
+    /// than the tenant's current release.
+    pub vms_version: Option<VmsVersion>,
 }
 
 #[derive(Debug, Display)]
@@ -27,7 +59,14 @@ pub enum UserError {
     UserNotFound,
     UserUpgradeFailure,
     UserCreationFailure,
+    UserLookupFailure,
     BadUserRequest,
+    IdempotencyKeyInFlight,
+    IdempotencyKeyReused,
+    UserPreconditionFailed,
+    ClusterUnavailable,
+    ClusterFailure,
+    NamespaceNotATenant,
 }
 
 impl ResponseError for UserError {
@@ -42,73 +81,661 @@ impl ResponseError for UserError {
             UserError::UserNotFound => StatusCode::NOT_FOUND,
             UserError::UserUpgradeFailure => StatusCode::FAILED_DEPENDENCY,
             UserError::UserCreationFailure => StatusCode::FAILED_DEPENDENCY,
+            UserError::UserLookupFailure => StatusCode::FAILED_DEPENDENCY,
             UserError::BadUserRequest => StatusCode::BAD_REQUEST,
+            UserError::IdempotencyKeyInFlight => StatusCode::CONFLICT,
+            UserError::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            UserError::UserPreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            UserError::ClusterUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            UserError::ClusterFailure => StatusCode::BAD_GATEWAY,
+            UserError::NamespaceNotATenant => StatusCode::CONFLICT,
+        }
+    }
+}
+
+/// Maps a failed Kubernetes call that has no tenant-specific meaning to a
+/// 503 when the cluster is unreachable and a 502 otherwise.
+pub fn cluster_error(error: KubeError) -> UserError {
+    match error {
+        KubeError::Unavailable => UserError::ClusterUnavailable,
+        _ => UserError::ClusterFailure,
+    }
+}
+
+/// Resolves the `If-Match` header against the tenant's current
+/// `resourceVersion`, returning the version the Kubernetes call must be
+/// conditioned on, or `None` when the client sent no precondition.
+async fn if_match_precondition(
+    kube_repo: &KubeRepository,
+    req: &HttpRequest,
+    user_global_id: &str,
+) -> Result<Option<String>, UserError> {
+    if !req.headers().contains_key(IF_MATCH) {
+        return Ok(None);
+    }
+    let if_match = IfMatch::parse(req).map_err(|_| UserError::BadUserRequest)?;
+    let resource_version = kube_repo
+        .get_user_resource_version(user_global_id.to_owned())
+        .await
+        .ok_or(UserError::UserNotFound)?;
+
+    let matches = match if_match {
+        IfMatch::Any => true,
+        IfMatch::Items(tags) => tags
+            .iter()
+            .any(|tag| tag.strong_eq(&EntityTag::new_strong(resource_version.clone()))),
+    };
+    if matches {
+        Ok(Some(resource_version))
+    } else {
+        Err(UserError::UserPreconditionFailed)
+    }
+}
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Parses an RFC 3339 timestamp, or a date taken as midnight UTC.
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let midnight = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)?;
+            Some(Utc.from_utc_datetime(&midnight))
+        })
+}
+
+fn created_at(user: &UserV2) -> Option<DateTime<Utc>> {
+    user.created_at.as_deref().and_then(parse_time)
+}
+
+/// Filters, sorts and pages `users` as requested, returning the page and the
+/// number of tenants matching the filters.
+pub fn query_users(
+    mut users: Vec<UserV2>,
+    query: &UserQuery,
+) -> Result<(Vec<UserV2>, usize), UserError> {
+    let parse_bound = |bound: &Option<String>| match bound {
+        Some(bound) => parse_time(bound).map(Some).ok_or(UserError::BadUserRequest),
+        None => Ok(None),
+    };
+    let created_after = parse_bound(&query.created_after)?;
+    let created_before = parse_bound(&query.created_before)?;
+
+    if let Some(search) = &query.search {
+        let search = search.to_lowercase();
+        users.retain(|user| {
+            user.user_name.to_lowercase().contains(&search)
+                || user
+                    .metadata
+                    .display_name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase().contains(&search))
+        });
+    }
+    if let Some(vms_version) = query.vms_version {
+        users.retain(|user| user.vms_version == vms_version);
+    }
+    if let Some(created_after) = created_after {
+        users.retain(|user| created_at(user).is_some_and(|created| created >= created_after));
+    }
+    if let Some(created_before) = created_before {
+        users.retain(|user| created_at(user).is_some_and(|created| created < created_before));
+    }
+
+    match query.sort.unwrap_or_default() {
+        SortField::Name => users.sort_by(|a, b| a.user_name.cmp(&b.user_name)),
+        SortField::Version => {
+            users.sort_by(|a, b| (a.vms_version, &a.user_name).cmp(&(b.vms_version, &b.user_name)))
+        }
+        SortField::Created => {
+            users.sort_by(|a, b| (created_at(a), &a.user_name).cmp(&(created_at(b), &b.user_name)))
         }
     }
+    if query.order.unwrap_or_default() == SortOrder::Desc {
+        users.reverse();
+    }
+
+    let total = users.len();
+    if let Some(page) = query.page {
+        let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        users = users
+            .into_iter()
+            .skip(page.saturating_sub(1) * per_page)
+            .take(per_page)
+            .collect();
+    }
+    Ok((users, total))
+}
+
+pub fn list_response<T: Serialize>(users: Vec<T>, total: usize) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((routes::TOTAL_COUNT_HEADER, total))
+        .json(users)
+}
+
+/// Looks up a tenant together with the ETag derived from its namespace
+/// `resourceVersion`.
+pub async fn find_user(
+    kube_repo: &KubeRepository,
+    user_global_id: &str,
+) -> Result<(UserV2, EntityTag), UserError> {
+    let resource_version = kube_repo
+        .get_user_resource_version(user_global_id.to_owned())
+        .await;
+    let user = kube_repo
+        .get_user(user_global_id.to_owned())
+        .await
+        .map_err(cluster_error)?;
+
+    match (user, resource_version) {
+        (Some(user), Some(resource_version)) => Ok((user, EntityTag::new_strong(resource_version))),
+        _ => Err(UserError::UserNotFound),
+    }
+}
+
+pub async fn create(
+    kube_repo: &KubeRepository,
+    idempotency: &IdempotencyStore,
+    req: &HttpRequest,
+    dry_run: bool,
+    request: &CreateUserRequest,
+) -> Result<UserV2, UserError> {
+    let user = User::new(request.user_name.clone(), request.vms_version);
+
+    let idempotency_key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(_) if dry_run => None,
+        Some(key) => match key.to_str() {
+            Ok(key) if !key.is_empty() => Some(key.to_owned()),
+            _ => return Err(UserError::BadUserRequest),
+        },
+        None => None,
+    };
+    let fingerprint = format!("{}/{}", user.get_global_id(), user.vms_version);
+
+    let reservation = match &idempotency_key {
+        Some(key) => match idempotency.begin(key, &fingerprint) {
+            Lookup::Fresh(reservation) => Some(reservation),
+            Lookup::Replay(user) => return Ok(*user),
+            Lookup::InFlight => return Err(UserError::IdempotencyKeyInFlight),
+            Lookup::Mismatch => return Err(UserError::IdempotencyKeyReused),
+        },
+        None => None,
+    };
+
+    // A failed or cancelled attempt drops the reservation, releasing the key.
+    let user = kube_repo
+        .create_user(user, dry_run)
+        .await
+        .map_err(|_| UserError::UserCreationFailure)?;
+    if let Some(reservation) = reservation {
+        reservation.complete(user.clone());
+    }
+    Ok(user)
+}
+
+pub async fn apply(
+    kube_repo: &KubeRepository,
+    user_global_id: &str,
+    dry_run: bool,
+    request: &ApplyUserRequest,
+) -> Result<(UserV2, ApplyOutcome), UserError> {
+    let mut user = User::new(user_global_id.to_owned(), request.vms_version);
+    if let Some(server_type) = &request.server_type {
+        user.server_type = server_type.clone();
+    }
+
+    kube_repo
+        .apply_user(user, dry_run)
+        .await
+        .map_err(|error| match error {
+            KubeError::Conflict => UserError::NamespaceNotATenant,
+            _ => UserError::UserCreationFailure,
+        })
+}
+
+pub fn apply_status(outcome: ApplyOutcome) -> StatusCode {
+    match outcome {
+        ApplyOutcome::Created => StatusCode::CREATED,
+        _ => StatusCode::OK,
+    }
+}
+
+pub async fn upgrade(
+    kube_repo: &KubeRepository,
+    req: &HttpRequest,
+    user_global_id: &str,
+    query: &UpgradeQuery,
+) -> Result<UserV2, UserError> {
+    let resource_version = if_match_precondition(kube_repo, req, user_global_id).await?;
+    let current = kube_repo
+        .get_user(user_global_id.to_owned())
+        .await
+        .map_err(cluster_error)?
+        .ok_or(UserError::UserNotFound)?;
+    let vms_version = query.vms_version.unwrap_or_else(VmsVersion::latest);
+    if vms_version <= current.vms_version {
+        return Err(UserError::BadUserRequest);
+    }
+
+    let user = kube_repo
+        .upgrade_user(
+            user_global_id.to_owned(),
+            vms_version,
+            resource_version,
+            query.dry_run,
+        )
+        .await;
+
+    match user {
+        Ok(user) => Ok(user),
+        Err(KubeError::NotFound) => Err(UserError::UserNotFound),
+        Err(KubeError::Conflict) => Err(UserError::UserPreconditionFailed),
+        Err(error) => Err(cluster_error(error)),
+    }
+}
+
+/// Longest notes accepted; annotations share a size limit per namespace.
+const MAX_NOTES_LENGTH: usize = 4096;
+
+fn valid_metadata(metadata: &UserMetadata) -> bool {
+    let valid_email = match metadata.owner_email.as_deref() {
+        None | Some("") => true,
+        Some(email) => match email.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.contains('@')
+                    && !email.contains(char::is_whitespace)
+            }
+            None => false,
+        },
+    };
+    let notes_length = metadata
+        .notes
+        .as_ref()
+        .map_or(0, |notes| notes.chars().count());
+    valid_email && notes_length <= MAX_NOTES_LENGTH
+}
+
+pub async fn update_metadata(
+    kube_repo: &KubeRepository,
+    req: &HttpRequest,
+    user_global_id: &str,
+    dry_run: bool,
+    metadata: &UserMetadata,
+) -> Result<UserV2, UserError> {
+    if !valid_metadata(metadata) {
+        return Err(UserError::BadUserRequest);
+    }
+    let resource_version = if_match_precondition(kube_repo, req, user_global_id).await?;
+    // Only tenant namespaces may be annotated.
+    kube_repo
+        .get_user(user_global_id.to_owned())
+        .await
+        .map_err(cluster_error)?
+        .ok_or(UserError::UserNotFound)?;
+
+    let user = kube_repo
+        .update_user_metadata(
+            user_global_id.to_lowercase(),
+            metadata.clone(),
+            resource_version,
+            dry_run,
+        )
+        .await;
+
+    match user {
+        Ok(user) => Ok(user),
+        Err(KubeError::NotFound) => Err(UserError::UserNotFound),
+        Err(KubeError::Conflict) => Err(UserError::UserPreconditionFailed),
+        Err(error) => Err(cluster_error(error)),
+    }
+}
+
+pub async fn delete(
+    kube_repo: &KubeRepository,
+    req: &HttpRequest,
+    user_global_id: &str,
+    dry_run: bool,
+) -> Result<UserV2, UserError> {
+    let resource_version = if_match_precondition(kube_repo, req, user_global_id).await?;
+    // Only tenant namespaces may be deleted.
+    kube_repo
+        .get_user(user_global_id.to_owned())
+        .await
+        .map_err(cluster_error)?
+        .ok_or(UserError::UserNotFound)?;
+
+    let user = kube_repo
+        .delete_user(user_global_id.to_lowercase(), resource_version, dry_run)
+        .await;
+
+    match user {
+        Ok(user) => Ok(user),
+        Err(KubeError::NotFound) => Err(UserError::UserNotFound),
+        Err(KubeError::Conflict) => Err(UserError::UserPreconditionFailed),
+        Err(error) => Err(cluster_error(error)),
+    }
 }
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(UserQuery),
+    responses(
+        (status = 200, description = "Matching tenants", body = [User],
+            headers(("X-Total-Count" = usize, description = "Tenants matching the filters"))),
+        (status = 400, description = "Unparseable creation date filter"),
+        (status = 502, description = "Tenants could not be listed"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
 #[get("/user")]
-pub async fn get_users(kube_repo: Data<KubeRepository>) -> Result<Json<Vec<User>>, UserError> {
+pub async fn get_users(
+    kube_repo: Data<KubeRepository>,
+    query: Query<UserQuery>,
+) -> Result<HttpResponse, UserError> {
     let user = kube_repo.get_users().await;
 
     match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(UserError::UserNotFound),
+        Ok(user) => {
+            let (page, total) = query_users(user, &query)?;
+            Ok(list_response(
+                page.into_iter().map(User::from).collect(),
+                total,
+            ))
+        }
+        Err(error) => Err(cluster_error(error)),
     }
 }
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(("user_global_id" = String, Path, description = "Tenant name")),
+    responses(
+        (status = 200, description = "The tenant", body = User,
+            headers(("ETag" = String, description = "Namespace resourceVersion"))),
+        (status = 404, description = "Tenant not found"),
+    )
+)]
 #[get("/user/{user_global_id}")]
 pub async fn get_user(
     kube_repo: Data<KubeRepository>,
     user_global_id: Path<String>,
-) -> Result<Json<User>, UserError> {
-    let user = kube_repo.get_user(user_global_id.to_string()).await;
-
-    match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(UserError::UserNotFound),
-    }
+) -> Result<HttpResponse, UserError> {
+    let (user, etag) = find_user(&kube_repo, &user_global_id).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(etag))
+        .json(User::from(user)))
 }
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(
+        DryRunQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the original response for repeated requests"),
+    ),
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "Tenant created", body = User),
+        (status = 409, description = "A request with the same Idempotency-Key is in flight"),
+        (status = 422, description = "Idempotency-Key reused with a different request"),
+        (status = 424, description = "Tenant could not be created"),
+    )
+)]
 #[post("/user")]
 pub async fn create_user(
     kube_repo: Data<KubeRepository>,
+    idempotency: Data<IdempotencyStore>,
+    req: HttpRequest,
+    query: Query<DryRunQuery>,
     request: Json<CreateUserRequest>,
 ) -> Result<Json<User>, UserError> {
-    let vms_version = match VmsVersion::from_str(&request.vms_version) {
-        Ok(vms_version) => vms_version,
-        Err(_) => return Err(UserError::UserCreationFailure),
-    };
-    let user = User::new(request.user_name.clone(), vms_version);
+    create(&kube_repo, &idempotency, &req, query.dry_run, &request)
+        .await
+        .map(|user| Json(user.into()))
+}
 
-    match kube_repo.create_user(user).await {
-        Ok(user) => Ok(Json(user)),
-        Err(_) => Err(UserError::UserCreationFailure),
-    }
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(("user_global_id" = String, Path, description = "Tenant name"), DryRunQuery),
+    request_body = ApplyUserRequest,
+    responses(
+        (status = 200, description = "Tenant changed or unchanged", body = ApplyUserResponse),
+        (status = 201, description = "Tenant created", body = ApplyUserResponse),
+        (status = 400, description = "Unknown VMS version"),
+        (status = 409, description = "A namespace of that name exists but is not a tenant"),
+        (status = 424, description = "Tenant could not be applied"),
+    )
+)]
+#[put("/user/{user_global_id}")]
+pub async fn apply_user(
+    kube_repo: Data<KubeRepository>,
+    user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
+    request: Json<ApplyUserRequest>,
+) -> Result<HttpResponse, UserError> {
+    let (user, result) = apply(&kube_repo, &user_global_id, query.dry_run, &request).await?;
+    Ok(
+        HttpResponse::build(apply_status(result)).json(ApplyUserResponse {
+            user: user.into(),
+            result,
+        }),
+    )
 }
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        UpgradeQuery,
+    ),
+    responses(
+        (status = 200, description = "Tenant upgraded", body = User),
+        (status = 400, description = "Target version is not newer than the current one"),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Upgrade rejected or failed in the cluster"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
 #[patch("/user/{user_global_id}")]
 pub async fn upgrade_user(
     kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
     user_global_id: Path<String>,
+    query: Query<UpgradeQuery>,
 ) -> Result<Json<User>, UserError> {
-    let user = kube_repo.upgrade_user(user_global_id.to_string()).await;
+    upgrade(&kube_repo, &req, &user_global_id, &query)
+        .await
+        .map(|user| Json(user.into()))
+}
 
-    match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(UserError::UserNotFound),
-    }
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        DryRunQuery,
+    ),
+    request_body(content = UserMetadata, description = "Fields to set; omitted fields are kept and empty strings remove a field"),
+    responses(
+        (status = 200, description = "Metadata updated", body = User),
+        (status = 400, description = "Invalid owner email or notes too long"),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Metadata could not be written"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[patch("/user/{user_global_id}/metadata")]
+pub async fn update_user_metadata(
+    kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
+    user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
+    request: Json<UserMetadata>,
+) -> Result<Json<User>, UserError> {
+    update_metadata(&kube_repo, &req, &user_global_id, query.dry_run, &request)
+        .await
+        .map(|user| Json(user.into()))
 }
 
+#[utoipa::path(
+    context_path = "/api/v1",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        DryRunQuery,
+    ),
+    responses(
+        (status = 200, description = "Tenant deleted", body = User),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Deletion rejected or failed in the cluster"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
 #[delete("/user/{user_global_id}")]
 pub async fn delete_user(
     kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
     user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
 ) -> Result<Json<User>, UserError> {
-    let user = kube_repo.delete_user(user_global_id.to_string()).await;
+    delete(&kube_repo, &req, &user_global_id, query.dry_run)
+        .await
+        .map(|user| Json(user.into()))
+}
 
-    match user {
-        Some(user) => Ok(Json(user)),
-        None => Err(UserError::UserNotFound),
+/// Marks responses of the v1 tenant routes, including the bare `/api` alias,
+/// as deprecated and links to their v2 successor.
+pub fn deprecate_v1<S>(
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    let path = req.path();
+    let successor = path
+        .strip_prefix(routes::V1)
+        .or_else(|| path.strip_prefix("/api"))
+        .and_then(|route| route.strip_prefix("/user"))
+        .map(|rest| format!("<{}{rest}>; rel=\"successor-version\"", routes::USERS_V2));
+
+    let call = srv.call(req);
+    async move {
+        let mut response = call.await?;
+        if let Some(successor) = successor.and_then(|link| HeaderValue::from_str(&link).ok()) {
+            let headers = response.headers_mut();
+            headers.insert(
+                HeaderName::from_static("deprecation"),
+                HeaderValue::from_static("true"),
+            );
+            headers.insert(LINK, successor);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_time, query_users, UserError};
+    use common::model::{
+        query::{SortField, SortOrder, UserQuery},
+        user::{UserMetadata, VmsVersion},
+        user_v2::{UserStatus, UserV2},
+    };
+    use k8s_openapi::chrono::{TimeZone, Utc};
+
+    fn user(user_name: &str, created_at: Option<&str>) -> UserV2 {
+        UserV2 {
+            user_name: user_name.to_owned(),
+            vms_version: VmsVersion::XProtect2024R1,
+            server_type: "vms".to_owned(),
+            status: UserStatus::Active,
+            created_at: created_at.map(str::to_owned),
+            updated_at: None,
+            owner: None,
+            metadata: UserMetadata::default(),
+            uid: None,
+        }
+    }
+
+    fn fleet() -> Vec<UserV2> {
+        vec![
+            user("bravo", Some("2024-03-01T12:00:00+00:00")),
+            user("alpha", Some("2024-01-15T08:30:00+00:00")),
+            user("charlie", None),
+            user("delta", Some("2024-02-01T00:00:00Z")),
+        ]
+    }
+
+    fn names(users: &[UserV2]) -> Vec<&str> {
+        users.iter().map(|user| user.user_name.as_str()).collect()
+    }
+
+    #[test]
+    fn times_are_rfc_3339_or_dates_at_midnight_utc() {
+        assert_eq!(
+            parse_time("2024-01-15T10:30:00+02:00"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 8, 30, 0).unwrap())
+        );
+        assert_eq!(
+            parse_time("2024-01-15"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap())
+        );
+        assert_eq!(parse_time("15/01/2024"), None);
+        assert_eq!(parse_time("2024-13-01"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
+    #[test]
+    fn creation_bounds_are_inclusive_after_and_exclusive_before() {
+        let query = UserQuery {
+            created_after: Some("2024-01-15T08:30:00Z".to_owned()),
+            created_before: Some("2024-03-01".to_owned()),
+            ..Default::default()
+        };
+        let (users, total) = query_users(fleet(), &query).unwrap();
+        assert_eq!(names(&users), ["alpha", "delta"]);
+        assert_eq!(total, 2);
+    }
+
+    #[test]
+    fn unparseable_creation_bounds_are_bad_requests() {
+        for query in [
+            UserQuery {
+                created_after: Some("yesterday".to_owned()),
+                ..Default::default()
+            },
+            UserQuery {
+                created_before: Some("2024-02-30".to_owned()),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                query_users(fleet(), &query),
+                Err(UserError::BadUserRequest)
+            ));
+        }
+    }
+
+    #[test]
+    fn sorting_by_creation_puts_unknown_times_first() {
+        let mut query = UserQuery {
+            sort: Some(SortField::Created),
+            ..Default::default()
+        };
+        let (users, _) = query_users(fleet(), &query).unwrap();
+        assert_eq!(names(&users), ["charlie", "alpha", "delta", "bravo"]);
+
+        query.order = Some(SortOrder::Desc);
+        let (users, _) = query_users(fleet(), &query).unwrap();
+        assert_eq!(names(&users), ["bravo", "delta", "alpha", "charlie"]);
     }
 }
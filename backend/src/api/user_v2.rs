@@ -0,0 +1,221 @@
+use crate::api::idempotency::IdempotencyStore;
+use crate::api::user::{
+    apply, apply_status, cluster_error, create, delete, find_user, list_response, query_users,
+    update_metadata, upgrade, DryRunQuery, UpgradeQuery, UserError,
+};
+use crate::repository::kub::{KubeError, KubeRepository};
+use actix_web::{
+    delete, get,
+    http::header::ETag,
+    patch, post, put,
+    web::{Data, Json, Path, Query},
+    HttpRequest, HttpResponse,
+};
+use common::model::{
+    details::UserDetails,
+    query::UserQuery,
+    user::{ApplyUserRequest, CreateUserRequest, UserMetadata},
+    user_v2::{ApplyUserResponseV2, UserV2},
+};
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(UserQuery),
+    responses(
+        (status = 200, description = "Matching tenants", body = [UserV2],
+            headers(("X-Total-Count" = usize, description = "Tenants matching the filters"))),
+        (status = 400, description = "Unparseable creation date filter"),
+        (status = 502, description = "Tenants could not be listed"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[get("/user")]
+pub async fn get_users_v2(
+    kube_repo: Data<KubeRepository>,
+    query: Query<UserQuery>,
+) -> Result<HttpResponse, UserError> {
+    match kube_repo.get_users().await {
+        Ok(users) => {
+            let (page, total) = query_users(users, &query)?;
+            Ok(list_response(page, total))
+        }
+        Err(error) => Err(cluster_error(error)),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(("user_global_id" = String, Path, description = "Tenant name")),
+    responses(
+        (status = 200, description = "The tenant", body = UserV2,
+            headers(("ETag" = String, description = "Namespace resourceVersion"))),
+        (status = 404, description = "Tenant not found"),
+    )
+)]
+#[get("/user/{user_global_id}")]
+pub async fn get_user_v2(
+    kube_repo: Data<KubeRepository>,
+    user_global_id: Path<String>,
+) -> Result<HttpResponse, UserError> {
+    let (user, etag) = find_user(&kube_repo, &user_global_id).await?;
+    Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(user))
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(("user_global_id" = String, Path, description = "Tenant name")),
+    responses(
+        (status = 200, description = "Namespace metadata, resources and recent events", body = UserDetails),
+        (status = 404, description = "Tenant not found"),
+        (status = 424, description = "Namespace could not be read"),
+    )
+)]
+#[get("/user/{user_global_id}/details")]
+pub async fn get_user_details_v2(
+    kube_repo: Data<KubeRepository>,
+    user_global_id: Path<String>,
+) -> Result<Json<UserDetails>, UserError> {
+    match kube_repo
+        .get_user_details(user_global_id.into_inner())
+        .await
+    {
+        Ok(details) => Ok(Json(details)),
+        Err(KubeError::NotFound) => Err(UserError::UserNotFound),
+        Err(_) => Err(UserError::UserLookupFailure),
+    }
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(
+        DryRunQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Replays the original response for repeated requests"),
+    ),
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "Tenant created", body = UserV2),
+        (status = 409, description = "A request with the same Idempotency-Key is in flight"),
+        (status = 422, description = "Idempotency-Key reused with a different request"),
+        (status = 424, description = "Tenant could not be created"),
+    )
+)]
+#[post("/user")]
+pub async fn create_user_v2(
+    kube_repo: Data<KubeRepository>,
+    idempotency: Data<IdempotencyStore>,
+    req: HttpRequest,
+    query: Query<DryRunQuery>,
+    request: Json<CreateUserRequest>,
+) -> Result<Json<UserV2>, UserError> {
+    create(&kube_repo, &idempotency, &req, query.dry_run, &request)
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(("user_global_id" = String, Path, description = "Tenant name"), DryRunQuery),
+    request_body = ApplyUserRequest,
+    responses(
+        (status = 200, description = "Tenant changed or unchanged", body = ApplyUserResponseV2),
+        (status = 201, description = "Tenant created", body = ApplyUserResponseV2),
+        (status = 400, description = "Unknown VMS version"),
+        (status = 409, description = "A namespace of that name exists but is not a tenant"),
+        (status = 424, description = "Tenant could not be applied"),
+    )
+)]
+#[put("/user/{user_global_id}")]
+pub async fn apply_user_v2(
+    kube_repo: Data<KubeRepository>,
+    user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
+    request: Json<ApplyUserRequest>,
+) -> Result<HttpResponse, UserError> {
+    let (user, result) = apply(&kube_repo, &user_global_id, query.dry_run, &request).await?;
+    Ok(HttpResponse::build(apply_status(result)).json(ApplyUserResponseV2 { user, result }))
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        UpgradeQuery,
+    ),
+    responses(
+        (status = 200, description = "Tenant upgraded", body = UserV2),
+        (status = 400, description = "Target version is not newer than the current one"),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Upgrade rejected or failed in the cluster"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[patch("/user/{user_global_id}")]
+pub async fn upgrade_user_v2(
+    kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
+    user_global_id: Path<String>,
+    query: Query<UpgradeQuery>,
+) -> Result<Json<UserV2>, UserError> {
+    upgrade(&kube_repo, &req, &user_global_id, &query)
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        DryRunQuery,
+    ),
+    request_body(content = UserMetadata, description = "Fields to set; omitted fields are kept and empty strings remove a field"),
+    responses(
+        (status = 200, description = "Metadata updated", body = UserV2),
+        (status = 400, description = "Invalid owner email or notes too long"),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Metadata could not be written"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[patch("/user/{user_global_id}/metadata")]
+pub async fn update_user_metadata_v2(
+    kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
+    user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
+    request: Json<UserMetadata>,
+) -> Result<Json<UserV2>, UserError> {
+    update_metadata(&kube_repo, &req, &user_global_id, query.dry_run, &request)
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    context_path = "/api/v2",
+    params(
+        ("user_global_id" = String, Path, description = "Tenant name"),
+        ("If-Match" = Option<String>, Header, description = "ETag the tenant must still have"),
+        DryRunQuery,
+    ),
+    responses(
+        (status = 200, description = "Tenant deleted", body = UserV2),
+        (status = 404, description = "Tenant not found"),
+        (status = 412, description = "Tenant changed since the given ETag"),
+        (status = 502, description = "Deletion rejected or failed in the cluster"),
+        (status = 503, description = "Cluster unreachable"),
+    )
+)]
+#[delete("/user/{user_global_id}")]
+pub async fn delete_user_v2(
+    kube_repo: Data<KubeRepository>,
+    req: HttpRequest,
+    user_global_id: Path<String>,
+    query: Query<DryRunQuery>,
+) -> Result<Json<UserV2>, UserError> {
+    delete(&kube_repo, &req, &user_global_id, query.dry_run)
+        .await
+        .map(Json)
+}
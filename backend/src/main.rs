@@ -1,32 +1,170 @@
 mod api;
+mod metrics;
+mod operations;
+mod rate_limit;
 mod repository;
+mod sync;
+mod telemetry;
+mod tls;
 
-use actix_web::{middleware::Logger, web::scope, web::Data, App, HttpServer};
+use actix_web::{
+    dev::Service,
+    web::{scope, Data, ServiceConfig},
+    App, HttpServer, ResponseError,
+};
 use actix_web_lab::web::spa;
-use api::user::{create_user, delete_user, get_user, get_users, upgrade_user};
+use api::auth::{get_session, hash_password_command, login, logout, require_session, SessionStore};
+use api::health::{healthz, readyz, PermissionCache};
+use api::idempotency::IdempotencyStore;
+use api::openapi::{api_docs, api_docs_script, api_docs_style, openapi_json};
+use api::stats::get_stats;
+use api::sync::post_sync;
+use api::user::{
+    apply_user, create_user, delete_user, deprecate_v1, get_user, get_users, update_user_metadata,
+    upgrade_user, UserError,
+};
+use api::user_v2::{
+    apply_user_v2, create_user_v2, delete_user_v2, get_user_details_v2, get_user_v2, get_users_v2,
+    update_user_metadata_v2, upgrade_user_v2,
+};
+use common::api::routes;
+use operations::OperationLog;
+use rate_limit::RateLimiter;
 use repository::kub::KubeRepository;
+use std::sync::Arc;
+use std::time::Instant;
+
+fn v1_routes(cfg: &mut ServiceConfig) {
+    cfg.service(get_users)
+        .service(get_user)
+        .service(create_user)
+        .service(apply_user)
+        .service(upgrade_user)
+        .service(update_user_metadata)
+        .service(delete_user)
+        .service(post_sync);
+}
+
+fn v2_routes(cfg: &mut ServiceConfig) {
+    cfg.service(get_users_v2)
+        .service(get_user_v2)
+        .service(get_user_details_v2)
+        .service(create_user_v2)
+        .service(apply_user_v2)
+        .service(upgrade_user_v2)
+        .service(update_user_metadata_v2)
+        .service(delete_user_v2);
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    std::env::set_var("RUST_LOG", "debug");
     std::env::set_var("RUST_BACKTRACE", "1");
-    env_logger::init();
+    let log_level = Data::new(telemetry::init());
 
-    let kube_repo: KubeRepository = KubeRepository::init().await?;
-    HttpServer::new(move || {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("sync") {
+        let kube_repo = KubeRepository::connect().await?;
+        return sync::sync_command(&kube_repo, &args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("hash-password") {
+        return hash_password_command();
+    }
+
+    let kube_repo: KubeRepository = KubeRepository::init();
+
+    let idempotency_data = Data::new(IdempotencyStore::from_env());
+    let operations_data = Data::new(OperationLog::new());
+    let permission_cache_data = Data::new(PermissionCache::default());
+    let sessions_data = Data::new(SessionStore::from_env()?);
+    let rate_limiter = Arc::new(RateLimiter::from_env());
+    let tls = tls::TlsSettings::from_env();
+    let https_redirect = tls
+        .as_ref()
+        .filter(|tls| tls.redirect_http)
+        .map(|tls| tls.port);
+    let http_port = std::env::var("KITODAR_HTTP_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(80);
+
+    let server = HttpServer::new(move || {
         let kube_data = Data::new(kube_repo.clone());
-        let logger = Logger::default();
+        let kube_guard = kube_repo.clone();
+        let rate_limiter = rate_limiter.clone();
+        let operations = operations_data.clone();
+        let sessions = sessions_data.clone();
 
         App::new()
-            .wrap(logger)
+            .wrap_fn(|req, srv| {
+                let started = Instant::now();
+                let response = srv.call(req);
+                async move {
+                    let response = response.await?;
+                    metrics::observe_http_request(&response, started);
+                    Ok(response)
+                }
+            })
+            .wrap_fn(telemetry::trace_request)
+            .wrap_fn(move |req, srv| tls::redirect_to_https(https_redirect, req, srv))
             .app_data(kube_data)
+            .app_data(idempotency_data.clone())
+            .app_data(operations_data.clone())
+            .app_data(permission_cache_data.clone())
+            .app_data(sessions_data.clone())
+            .app_data(log_level.clone())
+            .service(metrics::metrics)
+            .service(healthz)
+            .service(readyz)
             .service(
                 scope("/api")
-                    .service(get_users)
-                    .service(get_user)
-                    .service(create_user)
-                    .service(upgrade_user)
-                    .service(delete_user),
+                    .wrap_fn(move |req, srv| {
+                        // Signing in, the API documentation and the log level do
+                        // not need the cluster.
+                        let call = if kube_guard.is_available()
+                            || [
+                                routes::LOGIN,
+                                routes::LOGOUT,
+                                routes::SESSION,
+                                routes::OPENAPI,
+                                routes::DOCS,
+                                routes::DOCS_SCRIPT,
+                                routes::DOCS_STYLE,
+                                routes::LOG_LEVEL,
+                            ]
+                            .contains(&req.path())
+                        {
+                            Ok(srv.call(req))
+                        } else {
+                            Err(req)
+                        };
+                        async move {
+                            match call {
+                                Ok(call) => call.await,
+                                Err(req) => Ok(req
+                                    .into_response(UserError::ClusterUnavailable.error_response())),
+                            }
+                        }
+                    })
+                    .wrap_fn(move |req, srv| rate_limit::limit_mutations(&rate_limiter, req, srv))
+                    .wrap_fn(deprecate_v1)
+                    .wrap_fn(move |req, srv| {
+                        operations::record_mutations(operations.clone(), req, srv)
+                    })
+                    .wrap_fn(move |req, srv| require_session(&sessions, req, srv))
+                    .service(scope("/v1").configure(v1_routes))
+                    .service(scope("/v2").configure(v2_routes))
+                    // Unversioned alias of v1 for existing scripts.
+                    .configure(v1_routes)
+                    .service(login)
+                    .service(logout)
+                    .service(get_session)
+                    .service(get_stats)
+                    .service(openapi_json)
+                    .service(api_docs)
+                    .service(api_docs_script)
+                    .service(api_docs_style)
+                    .service(telemetry::get_log_level)
+                    .service(telemetry::set_log_level),
             )
             .service(
                 spa()
@@ -36,7 +174,14 @@ async fn main() -> std::io::Result<()> {
                     .finish(),
             )
     })
-    .bind(("0.0.0.0", 80))?
-    .run()
-    .await
+    .bind(("0.0.0.0", http_port))?;
+
+    let server = match tls {
+        Some(tls) => {
+            let https_port = tls.port;
+            server.bind_rustls_021(("0.0.0.0", https_port), tls::server_config(tls)?)?
+        }
+        None => server,
+    };
+    server.run().await
 }
@@ -0,0 +1,110 @@
+use crate::repository::kub::KubeRepository;
+use actix_web::{dev::ServiceResponse, get, http::header::ContentType, web::Data, HttpResponse};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, Encoder,
+    HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+use std::{future::Future, time::Instant};
+
+lazy_static! {
+    static ref HTTP_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "kitodar_http_requests_total",
+        "HTTP requests handled, by route, method and status",
+        &["route", "method", "status"]
+    )
+    .unwrap();
+    static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "kitodar_http_request_duration_seconds",
+        "HTTP request latency, by route and method",
+        &["route", "method"]
+    )
+    .unwrap();
+    static ref KUBE_CALL_DURATION: HistogramVec = register_histogram_vec!(
+        "kitodar_kube_call_duration_seconds",
+        "Kubernetes API call latency, by KubeRepository method",
+        &["method"]
+    )
+    .unwrap();
+    static ref KUBE_CALL_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "kitodar_kube_call_errors_total",
+        "Failed Kubernetes API calls, by KubeRepository method",
+        &["method"]
+    )
+    .unwrap();
+    static ref TENANTS_BY_VERSION: IntGaugeVec = register_int_gauge_vec!(
+        "kitodar_tenants_by_vms_version",
+        "Tenants per VMS version",
+        &["vms_version"]
+    )
+    .unwrap();
+    static ref TENANTS_BY_SERVER_TYPE: IntGaugeVec = register_int_gauge_vec!(
+        "kitodar_tenants_by_server_type",
+        "Tenants per server type",
+        &["server_type"]
+    )
+    .unwrap();
+}
+
+pub fn observe_http_request<B>(response: &ServiceResponse<B>, started: Instant) {
+    let request = response.request();
+    let route = request
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let method = request.method().as_str();
+
+    HTTP_REQUESTS
+        .with_label_values(&[&route, method, response.status().as_str()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[&route, method])
+        .observe(started.elapsed().as_secs_f64());
+}
+
+/// Times a single Kubernetes API call made on behalf of `method`.
+pub async fn observe_kube_call<T>(
+    method: &'static str,
+    call: impl Future<Output = Result<T, kube::Error>>,
+) -> Result<T, kube::Error> {
+    let started = Instant::now();
+    let result = call.await;
+    KUBE_CALL_DURATION
+        .with_label_values(&[method])
+        .observe(started.elapsed().as_secs_f64());
+    if result.is_err() {
+        KUBE_CALL_ERRORS.with_label_values(&[method]).inc();
+    }
+    result
+}
+
+/// Refreshes the fleet gauges from the cluster on every scrape, keeping the
+/// last known values when the tenants cannot be listed.
+async fn update_fleet_gauges(kube_repo: &KubeRepository) {
+    let Ok(users) = kube_repo.get_users().await else {
+        return;
+    };
+
+    TENANTS_BY_VERSION.reset();
+    TENANTS_BY_SERVER_TYPE.reset();
+    for user in users {
+        TENANTS_BY_VERSION
+            .with_label_values(&[&user.vms_version.to_string()])
+            .inc();
+        TENANTS_BY_SERVER_TYPE
+            .with_label_values(&[&user.server_type])
+            .inc();
+    }
+}
+
+#[get("/metrics")]
+pub async fn metrics(kube_repo: Data<KubeRepository>) -> HttpResponse {
+    update_fleet_gauges(&kube_repo).await;
+
+    let mut buffer = Vec::new();
+    match TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => HttpResponse::Ok()
+            .insert_header(ContentType::plaintext())
+            .body(buffer),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
@@ -0,0 +1,135 @@
+use crate::api::user::DryRunQuery;
+use crate::telemetry::REQUEST_ID_HEADER;
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse},
+    http::Method,
+    web::{Data, Query},
+};
+use common::model::stats::Operation;
+use std::{collections::VecDeque, future::Future, sync::Mutex};
+
+/// Operations kept for the dashboard; older ones are dropped.
+const CAPACITY: usize = 50;
+
+/// In-memory record of the most recent mutating API calls. It is per replica
+/// and lost on restart.
+pub struct OperationLog {
+    operations: Mutex<VecDeque<Operation>>,
+}
+
+impl OperationLog {
+    pub fn new() -> OperationLog {
+        OperationLog {
+            operations: Mutex::new(VecDeque::with_capacity(CAPACITY)),
+        }
+    }
+
+    fn record(&self, operation: Operation) {
+        let mut operations = self.operations.lock().unwrap();
+        if operations.len() == CAPACITY {
+            operations.pop_back();
+        }
+        operations.push_front(operation);
+    }
+
+    /// Newest first.
+    pub fn recent(&self) -> Vec<Operation> {
+        self.operations.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Tenant and sync routes, in v1, v2 and the unversioned alias. Signing in
+/// and other administrative calls are not tenant operations.
+fn is_tenant_route(path: &str) -> bool {
+    let Some(route) = path.strip_prefix("/api") else {
+        return false;
+    };
+    let route = route
+        .strip_prefix("/v1")
+        .or_else(|| route.strip_prefix("/v2"))
+        .unwrap_or(route);
+    route == "/user" || route.starts_with("/user/") || route == "/sync"
+}
+
+/// Reads `dryRun` the way the handlers do, so a malformed value, which they
+/// reject, does not count as a dry run.
+fn is_dry_run(query_string: &str) -> bool {
+    Query::<DryRunQuery>::from_query(query_string).is_ok_and(|query| query.dry_run)
+}
+
+/// Records every non-dry-run POST, PUT, PATCH and DELETE on a tenant route
+/// once answered.
+pub fn record_mutations<S>(
+    log: Data<OperationLog>,
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    let mutation = matches!(
+        *req.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    ) && is_tenant_route(req.path())
+        && !is_dry_run(req.query_string());
+    let method = req.method().to_string();
+    let path = req.path().to_owned();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .map(str::to_owned);
+
+    let call = srv.call(req);
+    async move {
+        let response = call.await?;
+        if mutation {
+            log.record(Operation {
+                at: k8s_openapi::chrono::Utc::now().to_rfc3339(),
+                method,
+                path,
+                status: response.status().as_u16(),
+                request_id,
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_dry_run, is_tenant_route};
+
+    #[test]
+    fn only_tenant_routes_are_operations() {
+        for path in [
+            "/api/user",
+            "/api/user/alice",
+            "/api/v1/user/alice/metadata",
+            "/api/v2/user",
+            "/api/v1/sync",
+        ] {
+            assert!(is_tenant_route(path), "{path}");
+        }
+        for path in [
+            "/api/login",
+            "/api/logout",
+            "/api/admin/log-level",
+            "/api/users",
+            "/user",
+        ] {
+            assert!(!is_tenant_route(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn dry_runs_are_parsed_like_the_handlers_do() {
+        assert!(is_dry_run("dryRun=true"));
+        assert!(is_dry_run("page=2&dryRun=true"));
+        assert!(!is_dry_run(""));
+        assert!(!is_dry_run("dryRun=false"));
+        assert!(!is_dry_run("xdryRun=true"));
+        assert!(!is_dry_run("dryRun=True"));
+        assert!(!is_dry_run("dryRun=1"));
+    }
+}
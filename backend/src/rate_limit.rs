@@ -0,0 +1,228 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse},
+    http::{header::RETRY_AFTER, Method},
+    HttpResponse,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// At most this many clients are tracked. Buckets that have refilled are
+/// pruned first, then the least recently used ones.
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Released when the mutation it was granted for has completed.
+pub struct MutationPermit(Arc<AtomicUsize>);
+
+impl Drop for MutationPermit {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Per-client token buckets for mutating requests, plus a global cap on the
+/// number of mutations in flight against the cluster.
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    max_concurrent: usize,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    in_flight: Arc<AtomicUsize>,
+    /// Peers whose `Forwarded`/`X-Forwarded-For` headers are believed.
+    trusted_proxies: Vec<IpAddr>,
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: u32, burst: u32, max_concurrent: usize) -> RateLimiter {
+        RateLimiter {
+            per_second: f64::from(per_minute.max(1)) / 60.0,
+            burst: f64::from(burst.max(1)),
+            max_concurrent: max_concurrent.max(1),
+            buckets: Mutex::new(HashMap::new()),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    pub fn from_env() -> RateLimiter {
+        RateLimiter {
+            trusted_proxies: std::env::var("KITODAR_TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|proxy| proxy.trim().parse().ok())
+                .collect(),
+            ..RateLimiter::new(
+                env_or("KITODAR_RATE_LIMIT_PER_MINUTE", 60),
+                env_or("KITODAR_RATE_LIMIT_BURST", 10),
+                env_or("KITODAR_MAX_CONCURRENT_MUTATIONS", 4),
+            )
+        }
+    }
+
+    /// The address a request is accounted to: the connecting peer or, when
+    /// that is a trusted proxy, the nearest `X-Forwarded-For` hop that is not
+    /// one. Hops further left are supplied by the client and never trusted.
+    fn client(&self, req: &ServiceRequest) -> String {
+        let Some(peer) = req.peer_addr().map(|peer| peer.ip()) else {
+            return "unknown".to_owned();
+        };
+        if !self.trusted_proxies.contains(&peer) {
+            return peer.to_string();
+        }
+        let forwarded_for: Vec<&str> = req
+            .headers()
+            .get_all("X-Forwarded-For")
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        forwarded_for
+            .into_iter()
+            .rev()
+            .find(|hop| {
+                hop.parse()
+                    .map_or(true, |hop: IpAddr| !self.trusted_proxies.contains(&hop))
+            })
+            .map_or_else(|| peer.to_string(), str::to_owned)
+    }
+
+    /// Admits a mutation from `client`, or returns how long it should wait.
+    pub fn admit(&self, client: &str) -> Result<MutationPermit, Duration> {
+        if self.in_flight.fetch_add(1, Ordering::SeqCst) >= self.max_concurrent {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            return Err(Duration::from_secs(1));
+        }
+        let permit = MutationPermit(self.in_flight.clone());
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            let refill = Duration::from_secs_f64(self.burst / self.per_second);
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated) < refill);
+            if buckets.len() >= MAX_TRACKED_CLIENTS {
+                let oldest = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.updated)
+                    .map(|(client, _)| client.clone());
+                if let Some(oldest) = oldest {
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+        let bucket = buckets.entry(client.to_owned()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(permit)
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.per_second;
+            Err(Duration::from_secs(wait.ceil() as u64))
+        }
+    }
+}
+
+fn is_mutation(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+/// Middleware answering 429 with `Retry-After` when a client exceeds its rate
+/// or too many mutations are already running.
+pub fn limit_mutations<S>(
+    limiter: &RateLimiter,
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    let admission = if is_mutation(req.method()) {
+        limiter.admit(&limiter.client(&req)).map(Some)
+    } else {
+        Ok(None)
+    };
+
+    let call = match admission {
+        Ok(permit) => Ok((permit, srv.call(req))),
+        Err(retry_after) => Err((req, retry_after)),
+    };
+    async move {
+        match call {
+            Ok((_permit, call)) => call.await,
+            Err((req, retry_after)) => {
+                tracing::warn!(?retry_after, "mutation rate limited");
+                Ok(req.into_response(
+                    HttpResponse::TooManyRequests()
+                        .insert_header((RETRY_AFTER, retry_after.as_secs().max(1)))
+                        .body("TooManyRequests"),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use actix_web::test::TestRequest;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter {
+            trusted_proxies: vec!["10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap()],
+            ..RateLimiter::new(60, 10, 4)
+        }
+    }
+
+    #[test]
+    fn untrusted_peers_cannot_choose_their_key() {
+        let req = TestRequest::default()
+            .peer_addr("203.0.113.7:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.1"))
+            .to_srv_request();
+        assert_eq!(limiter().client(&req), "203.0.113.7");
+    }
+
+    #[test]
+    fn spoofed_hops_left_of_the_proxy_are_ignored() {
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", "198.51.100.1, 203.0.113.7, 10.0.0.2"))
+            .to_srv_request();
+        assert_eq!(limiter().client(&req), "203.0.113.7");
+    }
+
+    #[test]
+    fn trusted_proxies_without_forwarding_are_the_client() {
+        let req = TestRequest::default()
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .to_srv_request();
+        assert_eq!(limiter().client(&req), "10.0.0.1");
+    }
+}
@@ -1,190 +1,719 @@
-use common::model::user::{User, VmsVersion};
-use k8s_openapi::api::core::v1::Namespace;
+use crate::metrics::observe_kube_call;
+use common::model::{
+    details::{NamespaceEvent, NamespaceResource, UserDetails},
+    user::{ApplyOutcome, User, UserMetadata, VmsVersion},
+    user_v2::{UserStatus, UserV2},
+};
+use k8s_openapi::{
+    api::{
+        apps::v1::{Deployment, StatefulSet},
+        authorization::v1::{
+            ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+        },
+        core::v1::{Event, Namespace, PersistentVolumeClaim, Pod, Service},
+    },
+    NamespaceResourceScope,
+};
 use kube::{
-    api::{Api, DeleteParams, ListParams, Patch, PatchParams, PostParams, ResourceExt},
-    core::{ObjectMeta, PartialObjectMetaExt},
-    Client,
+    api::{
+        Api, DeleteParams, ListParams, Patch, PatchParams, PostParams, Preconditions, ResourceExt,
+    },
+    core::ObjectMeta,
+    Client, Resource,
 };
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use std::{
+    collections::BTreeSet,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+const FIELD_MANAGER: &str = "kitodar";
 
-use std::str::FromStr;
+/// Events shown on the tenant detail page, newest first.
+const MAX_EVENTS: usize = 20;
 
+const DISPLAY_NAME_ANNOTATION: &str = "vms.myenv.cloud/display-name";
+const OWNER_EMAIL_ANNOTATION: &str = "vms.myenv.cloud/owner-email";
+const COST_CENTER_ANNOTATION: &str = "vms.myenv.cloud/cost-center";
+const NOTES_ANNOTATION: &str = "vms.myenv.cloud/notes";
+
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often a connected API server is checked for an outage.
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Handle to the cluster. The client is filled in once the API server has
+/// been reached and withdrawn while it is unreachable, so the rest of the
+/// application can run without it.
 #[derive(Clone)]
 pub struct KubeRepository {
-    client: Client,
+    client: Arc<RwLock<Option<Client>>>,
+}
+
+#[derive(Debug)]
+pub enum KubeError {
+    NotFound,
+    Conflict,
+    Unavailable,
+    Failure,
+}
+
+impl From<kube::Error> for KubeError {
+    fn from(error: kube::Error) -> Self {
+        match error {
+            kube::Error::Api(response) if response.code == 404 => KubeError::NotFound,
+            kube::Error::Api(response) if response.code == 409 => KubeError::Conflict,
+            _ => KubeError::Failure,
+        }
+    }
+}
+
+fn user_namespace(user: &User) -> Namespace {
+    Namespace {
+        metadata: ObjectMeta {
+            name: Some(user.get_global_id()),
+            labels: Some(std::collections::BTreeMap::from_iter(vec![
+                (
+                    "vms.myenv.cloud/user-namespace".to_string(),
+                    "true".to_string(),
+                ),
+                (
+                    "vms.myenv.cloud/vms-version".to_string(),
+                    user.vms_version.to_string(),
+                ),
+                (
+                    "vms.myenv.cloud/server-type".to_string(),
+                    user.server_type.clone(),
+                ),
+            ])),
+            ..Default::default()
+        },
+        spec: None,
+        status: None,
+    }
+}
+
+/// Whether kitodar created `namespace` for a tenant, as opposed to a system
+/// or foreign namespace that merely shares the name.
+fn is_tenant_namespace(namespace: &Namespace) -> bool {
+    namespace
+        .labels()
+        .contains_key("vms.myenv.cloud/user-namespace")
+}
+
+fn user_from_namespace(n: &Namespace) -> Option<UserV2> {
+    let vms_version = n.labels().get("vms.myenv.cloud/vms-version")?;
+    let server_type = n.labels().get("vms.myenv.cloud/server-type")?;
+
+    let status = match n.status.as_ref().and_then(|status| status.phase.as_deref()) {
+        Some("Active") => UserStatus::Active,
+        Some("Terminating") => UserStatus::Terminating,
+        _ => UserStatus::Unknown,
+    };
+    // The most recent write by any field manager.
+    let updated_at = n
+        .managed_fields()
+        .iter()
+        .filter_map(|entry| entry.time.as_ref())
+        .map(|time| time.0)
+        .max();
+
+    Some(UserV2 {
+        user_name: n.name_any(),
+        vms_version: VmsVersion::from_str(vms_version).ok()?,
+        server_type: server_type.to_owned(),
+        status,
+        created_at: n.creation_timestamp().map(|time| time.0.to_rfc3339()),
+        updated_at: updated_at.map(|time| time.to_rfc3339()),
+        owner: n.annotations().get(OWNER_EMAIL_ANNOTATION).cloned(),
+        metadata: UserMetadata {
+            display_name: n.annotations().get(DISPLAY_NAME_ANNOTATION).cloned(),
+            owner_email: n.annotations().get(OWNER_EMAIL_ANNOTATION).cloned(),
+            cost_center: n.annotations().get(COST_CENTER_ANNOTATION).cloned(),
+            notes: n.annotations().get(NOTES_ANNOTATION).cloned(),
+        },
+        uid: n.uid(),
+    })
+}
+
+/// Lists every object of kind `K` in `namespace` with a one-word status.
+async fn namespace_resources<K>(
+    client: &Client,
+    namespace: &str,
+    unavailable: &mut Vec<String>,
+    status: impl Fn(&K) -> Option<String>,
+) -> Vec<NamespaceResource>
+where
+    K: Resource<Scope = NamespaceResourceScope> + Clone + DeserializeOwned + std::fmt::Debug,
+    K::DynamicType: Default,
+{
+    let kind = K::kind(&K::DynamicType::default()).into_owned();
+    let api: Api<K> = Api::namespaced(client.clone(), namespace);
+    match observe_kube_call("get_user_details", api.list(&ListParams::default())).await {
+        Ok(list) => list
+            .items
+            .iter()
+            .map(|object| NamespaceResource {
+                kind: kind.clone(),
+                name: object.name_any(),
+                status: status(object),
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("could not list {kind} in {namespace}: {e}");
+            unavailable.push(kind);
+            Vec::new()
+        }
+    }
+}
+
+fn replicas(ready: Option<i32>, desired: Option<i32>) -> Option<String> {
+    Some(format!("{}/{}", ready.unwrap_or(0), desired.unwrap_or(0)))
 }
 
-pub struct KubeError;
 impl KubeRepository {
-    pub async fn init() -> std::io::Result<KubeRepository> {
-        let client = Client::try_default().await.unwrap();
-        Ok(KubeRepository { client })
+    /// Returns immediately and keeps probing the API server in the
+    /// background: until it is reached, backing off exponentially between
+    /// attempts, and afterwards every [`PROBE_INTERVAL`], so requests fail
+    /// with `Unavailable` during an outage and recover once it ends.
+    pub fn init() -> KubeRepository {
+        let repository = KubeRepository {
+            client: Arc::new(RwLock::new(None)),
+        };
+
+        let background = repository.clone();
+        actix_web::rt::spawn(async move {
+            let mut connected: Option<Client> = None;
+            let mut backoff = Duration::from_secs(1);
+            loop {
+                let probe = match &connected {
+                    Some(client) => client.apiserver_version().await.map(|_| client.clone()),
+                    None => Self::try_connect().await,
+                };
+                match probe {
+                    Ok(client) => {
+                        if !background.is_available() {
+                            tracing::info!("connected to the Kubernetes API server");
+                        }
+                        *background.client.write().unwrap() = Some(client.clone());
+                        connected = Some(client);
+                        backoff = Duration::from_secs(1);
+                        actix_web::rt::time::sleep(PROBE_INTERVAL).await;
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Kubernetes API server unavailable, retrying in {backoff:?}: {e}"
+                        );
+                        *background.client.write().unwrap() = None;
+                        actix_web::rt::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_CONNECT_BACKOFF);
+                    }
+                }
+            }
+        });
+
+        repository
     }
 
-    pub async fn create_user(&self, user: User) -> Result<User, KubeError> {
-        let client = self.client.clone();
+    /// Connects once, for one-shot commands that should fail fast.
+    pub async fn connect() -> std::io::Result<KubeRepository> {
+        let client = Self::try_connect()
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::ConnectionRefused, e))?;
+        Ok(KubeRepository {
+            client: Arc::new(RwLock::new(Some(client))),
+        })
+    }
+
+    async fn try_connect() -> Result<Client, kube::Error> {
+        let client = Client::try_default().await?;
+        client.apiserver_version().await?;
+        Ok(client)
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.client.read().unwrap().is_some()
+    }
+
+    fn client(&self) -> Result<Client, KubeError> {
+        self.client
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or(KubeError::Unavailable)
+    }
+
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user.get_global_id(), verb = "create")
+    )]
+    pub async fn create_user(&self, user: User, dry_run: bool) -> Result<UserV2, KubeError> {
+        let client = self.client()?;
 
         let namespaces: Api<Namespace> = Api::all(client);
-        let namespace = Namespace {
-            metadata: ObjectMeta {
-                name: Some(user.get_global_id()),
-                labels: Some(std::collections::BTreeMap::from_iter(vec![
-                    (
-                        "vms.myenv.cloud/user-namespace".to_string(),
-                        "true".to_string(),
-                    ),
-                    (
-                        "vms.myenv.cloud/vms-version".to_string(),
-                        user.vms_version.to_string(),
-                    ),
-                    (
-                        "vms.myenv.cloud/server-type".to_string(),
-                        user.server_type.clone(),
-                    ),
-                ])),
-                ..Default::default()
-            },
-            spec: None,
-            status: None,
+        let namespace = user_namespace(&user);
+
+        let post_param = PostParams {
+            dry_run,
+            ..Default::default()
         };
+        match observe_kube_call("create_user", namespaces.create(&post_param, &namespace)).await {
+            Ok(n) => user_from_namespace(&n).ok_or(KubeError::Failure),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Creates or updates the tenant's namespace through server-side apply,
+    /// taking ownership of the kitodar labels.
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user.get_global_id(), verb = "apply")
+    )]
+    pub async fn apply_user(
+        &self,
+        user: User,
+        dry_run: bool,
+    ) -> Result<(UserV2, ApplyOutcome), KubeError> {
+        let client = self.client()?;
+
+        let namespaces: Api<Namespace> = Api::all(client);
+        let name = user.get_global_id();
+        let previous = observe_kube_call("apply_user", namespaces.get_opt(&name)).await?;
+        // Never adopt a namespace kitodar did not create.
+        if previous
+            .as_ref()
+            .is_some_and(|previous| !is_tenant_namespace(previous))
+        {
+            return Err(KubeError::Conflict);
+        }
+        let namespace = user_namespace(&user);
 
-        match namespaces.create(&PostParams::default(), &namespace).await {
-            Ok(_) => Ok(user),
-            Err(_) => Err(KubeError),
+        let mut patch_param = PatchParams::apply(FIELD_MANAGER).force();
+        if dry_run {
+            patch_param = patch_param.dry_run();
         }
+        let applied = observe_kube_call(
+            "apply_user",
+            namespaces.patch(&name, &patch_param, &Patch::Apply(&namespace)),
+        )
+        .await?;
+        let applied = user_from_namespace(&applied).ok_or(KubeError::Failure)?;
+
+        // Compare the managed labels rather than resourceVersion, which a
+        // dry-run apply never bumps.
+        let managed = |user: &UserV2| (user.vms_version, user.server_type.clone());
+        let outcome = match previous {
+            None => ApplyOutcome::Created,
+            Some(p) if user_from_namespace(&p).map(|p| managed(&p)) != Some(managed(&applied)) => {
+                ApplyOutcome::Changed
+            }
+            Some(_) => ApplyOutcome::Unchanged,
+        };
+        Ok((applied, outcome))
     }
 
-    pub async fn get_users(&self) -> Option<Vec<User>> {
-        let mut result: Vec<User> = Vec::new();
-        let client = self.client.clone();
+    #[tracing::instrument(skip_all, err(Debug), fields(verb = "list"))]
+    pub async fn get_users(&self) -> Result<Vec<UserV2>, KubeError> {
+        let client = self.client()?;
 
         let namespaces: Api<Namespace> = Api::all(client);
         let list_param = ListParams {
             label_selector: Some("vms.myenv.cloud/user-namespace=true".to_owned()),
             ..Default::default()
         };
-        for n in namespaces.list(&list_param).await.unwrap() {
-            let user_name = n.name_any();
-            //let (user_uuid, user_name) = name_any.split_once('_').unwrap_or_default();
-
-            let (_, vms_version) = n
-                .labels()
-                .get_key_value("vms.myenv.cloud/vms-version")
-                .unwrap();
-            let (_, server_type) = n
-                .labels()
-                .get_key_value("vms.myenv.cloud/server-type")
-                .unwrap();
-
-            result.push(User {
-                user_name: user_name.to_owned(),
-                vms_version: VmsVersion::from_str(vms_version).unwrap(),
-                server_type: server_type.to_owned(),
+        Ok(observe_kube_call("get_users", namespaces.list(&list_param))
+            .await?
+            .iter()
+            .filter_map(user_from_namespace)
+            .collect())
+    }
+
+    /// Those of `namespaces` with a Deployment or StatefulSet that has fewer
+    /// ready replicas than it asks for. Only tenant namespaces are passed, so
+    /// workloads elsewhere in the cluster are never listed.
+    #[tracing::instrument(skip_all, err(Debug), fields(verb = "list"))]
+    pub async fn get_unready_namespaces(
+        &self,
+        namespaces: &[String],
+    ) -> Result<BTreeSet<String>, KubeError> {
+        let client = self.client()?;
+        let list_params = ListParams::default();
+        let unready =
+            |desired: Option<i32>, ready: Option<i32>| ready.unwrap_or(0) < desired.unwrap_or(1);
+
+        let mut unready_namespaces = BTreeSet::new();
+        for namespace in namespaces {
+            let deployments: Api<Deployment> = Api::namespaced(client.clone(), namespace);
+            let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+            let deployments =
+                observe_kube_call("get_unready_namespaces", deployments.list(&list_params)).await?;
+            let statefulsets =
+                observe_kube_call("get_unready_namespaces", statefulsets.list(&list_params))
+                    .await?;
+            let unready_deployment = deployments.iter().any(|deployment| {
+                unready(
+                    deployment.spec.as_ref().and_then(|spec| spec.replicas),
+                    deployment
+                        .status
+                        .as_ref()
+                        .and_then(|status| status.ready_replicas),
+                )
             });
+            let unready_statefulset = statefulsets.iter().any(|statefulset| {
+                unready(
+                    statefulset.spec.as_ref().and_then(|spec| spec.replicas),
+                    statefulset
+                        .status
+                        .as_ref()
+                        .and_then(|status| status.ready_replicas),
+                )
+            });
+            if unready_deployment || unready_statefulset {
+                unready_namespaces.insert(namespace.clone());
+            }
         }
-        if !result.is_empty() {
-            Some(result)
-        } else {
-            None
-        }
+        Ok(unready_namespaces)
     }
 
-    pub async fn upgrade_user(&self, user_global_id: String) -> Option<User> {
-        let client = self.client.clone();
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user_global_id, verb = "patch")
+    )]
+    pub async fn upgrade_user(
+        &self,
+        user_global_id: String,
+        vms_version: VmsVersion,
+        resource_version: Option<String>,
+        dry_run: bool,
+    ) -> Result<UserV2, KubeError> {
+        let client = self.client()?;
 
         let namespaces: Api<Namespace> = Api::all(client);
 
-        let patch = ObjectMeta {
-            labels: Some(
-                [(
-                    "vms.myenv.cloud/vms-version".to_string(),
-                    "XProtect2024R1".to_string(),
-                )]
-                .into(),
+        // A merge patch leaves the labels owned through server-side apply
+        // untouched; resourceVersion still makes it conditional.
+        let patch = Namespace {
+            metadata: ObjectMeta {
+                resource_version,
+                labels: Some(
+                    [(
+                        "vms.myenv.cloud/vms-version".to_string(),
+                        vms_version.to_string(),
+                    )]
+                    .into(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        match observe_kube_call(
+            "upgrade_user",
+            namespaces.patch(
+                &user_global_id,
+                &PatchParams {
+                    dry_run,
+                    field_manager: Some(FIELD_MANAGER.to_string()),
+                    ..Default::default()
+                },
+                &Patch::Merge(&patch),
             ),
+        )
+        .await
+        {
+            Ok(n) => user_from_namespace(&n).ok_or(KubeError::Failure),
+            Err(e) => Err(e.into()),
+        }
+    }
 
-            ..Default::default()
+    /// Sets the metadata annotations of the fields present in `metadata`,
+    /// removing those given as an empty string.
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user_global_id, verb = "patch")
+    )]
+    pub async fn update_user_metadata(
+        &self,
+        user_global_id: String,
+        metadata: UserMetadata,
+        resource_version: Option<String>,
+        dry_run: bool,
+    ) -> Result<UserV2, KubeError> {
+        let client = self.client()?;
+
+        let namespaces: Api<Namespace> = Api::all(client);
+
+        let annotations: Map<String, Value> = [
+            (DISPLAY_NAME_ANNOTATION, metadata.display_name),
+            (OWNER_EMAIL_ANNOTATION, metadata.owner_email),
+            (COST_CENTER_ANNOTATION, metadata.cost_center),
+            (NOTES_ANNOTATION, metadata.notes),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            // A JSON merge patch removes keys set to null.
+            let value = match value? {
+                value if value.is_empty() => Value::Null,
+                value => Value::String(value),
+            };
+            Some((key.to_owned(), value))
+        })
+        .collect();
+        let mut patch_metadata = json!({ "annotations": annotations });
+        if let Some(resource_version) = resource_version {
+            patch_metadata["resourceVersion"] = Value::String(resource_version);
         }
-        .into_request_partial::<Namespace>();
 
-        match namespaces
-            .patch_metadata(
+        match observe_kube_call(
+            "update_user_metadata",
+            namespaces.patch(
                 &user_global_id,
                 &PatchParams {
-                    force: true,
-                    field_manager: Some("application/apply-patch".to_string()),
+                    dry_run,
+                    field_manager: Some(FIELD_MANAGER.to_string()),
                     ..Default::default()
                 },
-                &Patch::Apply(patch),
-            )
-            .await
+                &Patch::Merge(json!({ "metadata": patch_metadata })),
+            ),
+        )
+        .await
         {
-            Ok(p) => {
-                let n = p.clone();
-                let user_name = n.name_any();
-
-                let namespace = n.clone();
-                let (_, server_type) = namespace
-                    .labels()
-                    .get_key_value("vms.myenv.cloud/server-type")
-                    .unwrap();
-
-                if let Some(x) = n
-                    .clone()
-                    .labels_mut()
-                    .get_mut("vms.myenv.cloud/vms-version")
-                {
-                    *x = VmsVersion::XProtect2024R1.to_string();
-                }
-
-                Some(User {
-                    user_name,
-                    vms_version: VmsVersion::XProtect2024R1,
-                    server_type: server_type.to_string(),
-                })
-            }
-            Err(_) => None,
+            Ok(n) => user_from_namespace(&n).ok_or(KubeError::Failure),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub async fn delete_user(&self, user_global_id: String) -> Option<User> {
-        let client = self.client.clone();
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user_global_id, verb = "delete")
+    )]
+    pub async fn delete_user(
+        &self,
+        user_global_id: String,
+        resource_version: Option<String>,
+        dry_run: bool,
+    ) -> Result<UserV2, KubeError> {
+        let client = self.client()?;
 
         let namespaces: Api<Namespace> = Api::all(client);
         let del_param = DeleteParams {
+            dry_run,
             grace_period_seconds: Some(0),
+            preconditions: resource_version.map(|resource_version| Preconditions {
+                resource_version: Some(resource_version),
+                uid: None,
+            }),
             ..Default::default()
         };
-        match namespaces.delete(&user_global_id, &del_param).await {
-            Ok(e) => {
-                let n = e.left().unwrap();
-                let user_name = n.name_any();
-
-                let (_, vms_version) = n
-                    .labels()
-                    .get_key_value("vms.myenv.cloud/vms-version")
-                    .unwrap();
-                let (_, server_type) = n
-                    .labels()
-                    .get_key_value("vms.myenv.cloud/server-type")
-                    .unwrap();
-
-                Some(User {
-                    user_name: user_name.to_owned(),
-                    vms_version: VmsVersion::from_str(vms_version).unwrap(),
-                    server_type: server_type.to_owned(),
-                })
-            }
-            Error => None,
+        match observe_kube_call(
+            "delete_user",
+            namespaces.delete(&user_global_id, &del_param),
+        )
+        .await
+        {
+            Ok(e) => match e.left() {
+                Some(n) => user_from_namespace(&n).ok_or(KubeError::Failure),
+                None => Err(KubeError::Failure),
+            },
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub async fn get_user(&self, user_global_id: String) -> Option<User> {
-        let result = self.get_users().await;
-        match result {
-            Some(users) => users
-                .into_iter()
-                .find(|user| user.user_name == user_global_id.to_lowercase()),
-            None => None,
+    #[tracing::instrument(skip_all, fields(namespace = %user_global_id, verb = "get"))]
+    pub async fn get_user(&self, user_global_id: String) -> Result<Option<UserV2>, KubeError> {
+        Ok(self
+            .get_users()
+            .await?
+            .into_iter()
+            .find(|user| user.user_name == user_global_id.to_lowercase()))
+    }
+
+    #[tracing::instrument(skip_all, fields(namespace = %user_global_id, verb = "get"))]
+    pub async fn get_user_resource_version(&self, user_global_id: String) -> Option<String> {
+        let client = self.client().ok()?;
+
+        let namespaces: Api<Namespace> = Api::all(client);
+        match observe_kube_call(
+            "get_user_resource_version",
+            namespaces.get_opt(&user_global_id.to_lowercase()),
+        )
+        .await
+        {
+            Ok(Some(n)) if is_tenant_namespace(&n) => n.resource_version(),
+            _ => None,
         }
     }
+
+    /// Collects the namespace metadata, the workloads running in it and its
+    /// most recent events.
+    #[tracing::instrument(
+        skip_all,
+        err(Debug),
+        fields(namespace = %user_global_id, verb = "get")
+    )]
+    pub async fn get_user_details(&self, user_global_id: String) -> Result<UserDetails, KubeError> {
+        let client = self.client()?;
+
+        let name = user_global_id.to_lowercase();
+        let namespaces: Api<Namespace> = Api::all(client.clone());
+        let namespace = observe_kube_call("get_user_details", namespaces.get_opt(&name))
+            .await?
+            .filter(is_tenant_namespace)
+            .ok_or(KubeError::NotFound)?;
+        let user = user_from_namespace(&namespace).ok_or(KubeError::Failure)?;
+
+        // Each kind is listed separately so that a missing permission only
+        // hides its own section.
+        let mut unavailable = Vec::new();
+        let mut resources = Vec::new();
+        resources.extend(
+            namespace_resources(&client, &name, &mut unavailable, |d: &Deployment| {
+                let status = d.status.as_ref()?;
+                replicas(status.ready_replicas, status.replicas)
+            })
+            .await,
+        );
+        resources.extend(
+            namespace_resources(&client, &name, &mut unavailable, |s: &StatefulSet| {
+                let status = s.status.as_ref()?;
+                replicas(status.ready_replicas, Some(status.replicas))
+            })
+            .await,
+        );
+        resources.extend(
+            namespace_resources(&client, &name, &mut unavailable, |p: &Pod| {
+                p.status.as_ref()?.phase.clone()
+            })
+            .await,
+        );
+        resources.extend(
+            namespace_resources(&client, &name, &mut unavailable, |s: &Service| {
+                s.spec.as_ref()?.type_.clone()
+            })
+            .await,
+        );
+        resources.extend(
+            namespace_resources(
+                &client,
+                &name,
+                &mut unavailable,
+                |c: &PersistentVolumeClaim| c.status.as_ref()?.phase.clone(),
+            )
+            .await,
+        );
+
+        let events: Api<Event> = Api::namespaced(client, &name);
+        let events = match observe_kube_call(
+            "get_user_details",
+            events.list(&ListParams::default()),
+        )
+        .await
+        {
+            Ok(events) => events.items,
+            Err(e) => {
+                tracing::warn!("could not list Event in {name}: {e}");
+                unavailable.push("Event".to_owned());
+                Vec::new()
+            }
+        };
+        let mut events: Vec<_> = events
+            .into_iter()
+            .map(|event| {
+                let last_seen = event
+                    .last_timestamp
+                    .map(|time| time.0)
+                    .or(event.event_time.map(|time| time.0));
+                let object = &event.involved_object;
+                let details = NamespaceEvent {
+                    event_type: event.type_.unwrap_or_default(),
+                    reason: event.reason.unwrap_or_default(),
+                    message: event.message.unwrap_or_default(),
+                    object: format!(
+                        "{}/{}",
+                        object.kind.as_deref().unwrap_or_default(),
+                        object.name.as_deref().unwrap_or_default()
+                    ),
+                    count: event.count.unwrap_or(1),
+                    last_seen: last_seen.map(|time| time.to_rfc3339()),
+                };
+                (last_seen, details)
+            })
+            .collect();
+        events.sort_by_key(|(last_seen, _)| std::cmp::Reverse(*last_seen));
+        events.truncate(MAX_EVENTS);
+
+        Ok(UserDetails {
+            user,
+            uid: namespace.uid(),
+            resource_version: namespace.resource_version(),
+            labels: namespace.labels().clone(),
+            annotations: namespace.annotations().clone(),
+            resources,
+            events: events.into_iter().map(|(_, event)| event).collect(),
+            unavailable,
+        })
+    }
+
+    #[tracing::instrument(skip_all, err(Debug))]
+    pub async fn api_server_version(&self) -> Result<String, KubeError> {
+        let client = self.client()?;
+        let info = observe_kube_call("api_server_version", client.apiserver_version()).await?;
+        Ok(info.git_version)
+    }
+
+    /// Asks the API server whether kitodar's own identity may perform `verb`
+    /// on cluster-scoped `resource`.
+    #[tracing::instrument(skip(self), err(Debug))]
+    pub async fn can_i(&self, group: &str, resource: &str, verb: &str) -> Result<bool, KubeError> {
+        let client = self.client()?;
+
+        let reviews: Api<SelfSubjectAccessReview> = Api::all(client);
+        let review = SelfSubjectAccessReview {
+            spec: SelfSubjectAccessReviewSpec {
+                resource_attributes: Some(ResourceAttributes {
+                    group: Some(group.to_owned()),
+                    verb: Some(verb.to_owned()),
+                    resource: Some(resource.to_owned()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let review =
+            observe_kube_call("can_i", reviews.create(&PostParams::default(), &review)).await?;
+        Ok(review.status.is_some_and(|status| status.allowed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_tenant_namespace, user_namespace};
+    use common::model::user::{User, VmsVersion};
+    use k8s_openapi::api::core::v1::Namespace;
+    use kube::core::ObjectMeta;
+
+    #[test]
+    fn only_labelled_namespaces_are_tenants() {
+        let tenant = user_namespace(&User::new("alice".to_owned(), VmsVersion::XProtect2024R1));
+        assert!(is_tenant_namespace(&tenant));
+
+        let system = Namespace {
+            metadata: ObjectMeta {
+                name: Some("kube-system".to_owned()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(!is_tenant_namespace(&system));
+    }
 }
@@ -0,0 +1,87 @@
+use crate::repository::kub::{KubeError, KubeRepository};
+use common::model::{
+    sync::{SyncDocument, SyncPlan, SyncResult},
+    user::User,
+};
+use std::io::{Error, ErrorKind};
+
+/// Converges the cluster on `document`. In dry-run mode only the plan is
+/// computed; otherwise every planned operation is attempted and the names of
+/// the tenants that failed are reported. Nothing is attempted when the
+/// current tenants cannot be listed, since every one of them would otherwise
+/// look missing.
+pub async fn sync_users(
+    kube_repo: &KubeRepository,
+    document: &SyncDocument,
+    dry_run: bool,
+    allow_delete: bool,
+) -> Result<SyncResult, KubeError> {
+    let current: Vec<User> = kube_repo
+        .get_users()
+        .await?
+        .into_iter()
+        .map(User::from)
+        .collect();
+    let plan = SyncPlan::compute(document, &current, allow_delete);
+    let mut failed = Vec::new();
+
+    if !dry_run {
+        for user in &plan.create {
+            if kube_repo.create_user(user.clone(), false).await.is_err() {
+                failed.push(user.user_name.clone());
+            }
+        }
+        for user in &plan.upgrade {
+            if kube_repo.apply_user(user.clone(), false).await.is_err() {
+                failed.push(user.user_name.clone());
+            }
+        }
+        for user in &plan.delete {
+            if kube_repo
+                .delete_user(user.get_global_id(), None, false)
+                .await
+                .is_err()
+            {
+                failed.push(user.user_name.clone());
+            }
+        }
+    }
+
+    Ok(SyncResult {
+        plan,
+        dry_run,
+        failed,
+    })
+}
+
+/// Command-line equivalent of `POST /api/sync`:
+/// `backend sync <file> [--dry-run] [--no-delete]`.
+pub async fn sync_command(kube_repo: &KubeRepository, args: &[String]) -> std::io::Result<()> {
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "usage: backend sync <file> [--dry-run] [--no-delete]",
+            )
+        })?;
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let allow_delete = !args.iter().any(|arg| arg == "--no-delete");
+
+    let document: SyncDocument = serde_yaml::from_slice(&std::fs::read(path)?)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let result = sync_users(kube_repo, &document, dry_run, allow_delete)
+        .await
+        .map_err(|e| Error::other(format!("could not list tenants: {e:?}")))?;
+    print!("{}", serde_yaml::to_string(&result).map_err(Error::other)?);
+
+    if result.failed.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::other(format!(
+            "sync failed for: {}",
+            result.failed.join(", ")
+        )))
+    }
+}
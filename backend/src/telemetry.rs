@@ -0,0 +1,107 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse},
+    get,
+    http::header::{HeaderName, HeaderValue},
+    put,
+    web::Data,
+    HttpResponse,
+};
+use std::{future::Future, time::Instant};
+use tracing::Instrument;
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Lets the log filter be changed while the server is running.
+#[derive(Clone)]
+pub struct LogLevelHandle(reload::Handle<EnvFilter, Registry>);
+
+/// Installs JSON logging filtered by `RUST_LOG` (default `info`). Records
+/// emitted through the `log` crate are forwarded as well.
+pub fn init() -> LogLevelHandle {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true),
+        )
+        .init();
+    LogLevelHandle(handle)
+}
+
+/// Runs every request inside a span carrying its request ID, reusing the
+/// caller's `X-Request-Id` when present, and echoes the ID in the response.
+pub fn trace_request<S, B>(
+    mut req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse<B>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    // Make a generated ID visible to handlers and inner middleware too.
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        req.headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    let span = tracing::info_span!(
+        "http_request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.path(),
+    );
+    let started = Instant::now();
+    let response = span.in_scope(|| srv.call(req));
+
+    async move {
+        let mut response = response.await?;
+        tracing::info!(
+            status = response.status().as_u16(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "request completed"
+        );
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+        }
+        Ok(response)
+    }
+    .instrument(span)
+}
+
+#[get("/admin/log-level")]
+pub async fn get_log_level(handle: Data<LogLevelHandle>) -> HttpResponse {
+    match handle.0.with_current(|filter| filter.to_string()) {
+        Ok(filter) => HttpResponse::Ok().body(filter),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Replaces the log filter with the directives in the body, e.g.
+/// `info,backend=debug,kube=trace`.
+#[put("/admin/log-level")]
+pub async fn set_log_level(handle: Data<LogLevelHandle>, body: String) -> HttpResponse {
+    let filter = match EnvFilter::try_new(body.trim()) {
+        Ok(filter) => filter,
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+    match handle.0.reload(filter) {
+        Ok(()) => {
+            tracing::info!(filter = body.trim(), "log level changed");
+            HttpResponse::Ok().body(body.trim().to_owned())
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
@@ -0,0 +1,182 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse},
+    http::header::LOCATION,
+    HttpResponse,
+};
+use rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+    Certificate, PrivateKey, ServerConfig,
+};
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufReader, Error, ErrorKind},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+/// Paths that keep answering over plain HTTP so probes and scrapers work
+/// when the redirect is enabled.
+const REDIRECT_EXEMPT: [&str; 3] = ["/healthz", "/readyz", "/metrics"];
+
+pub struct TlsSettings {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    pub port: u16,
+    pub redirect_http: bool,
+    pub reload_interval: Duration,
+}
+
+impl TlsSettings {
+    /// HTTPS is enabled when both `KITODAR_TLS_CERT` and `KITODAR_TLS_KEY`
+    /// are set.
+    pub fn from_env() -> Option<TlsSettings> {
+        let cert_path = std::env::var_os("KITODAR_TLS_CERT")?.into();
+        let key_path = std::env::var_os("KITODAR_TLS_KEY")?.into();
+        let env = |name: &str| std::env::var(name).ok();
+
+        Some(TlsSettings {
+            cert_path,
+            key_path,
+            port: env("KITODAR_HTTPS_PORT")
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(443),
+            redirect_http: env("KITODAR_HTTPS_REDIRECT").is_some_and(|value| value == "true"),
+            reload_interval: Duration::from_secs(
+                env("KITODAR_TLS_RELOAD_SECS")
+                    .and_then(|secs| secs.parse().ok())
+                    .unwrap_or(30),
+            ),
+        })
+    }
+}
+
+fn load_certified_key(settings: &TlsSettings) -> std::io::Result<CertifiedKey> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(&settings.cert_path)?))?
+        .into_iter()
+        .map(Certificate)
+        .collect::<Vec<_>>();
+    if certs.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, "no certificate found"));
+    }
+
+    let key = rustls_pemfile::read_all(&mut BufReader::new(File::open(&settings.key_path)?))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "no private key found"))?;
+    let key = sign::any_supported_type(&key)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+    Ok(CertifiedKey::new(certs, key))
+}
+
+fn modified(settings: &TlsSettings) -> Option<(SystemTime, SystemTime)> {
+    let cert = std::fs::metadata(&settings.cert_path)
+        .ok()?
+        .modified()
+        .ok()?;
+    let key = std::fs::metadata(&settings.key_path)
+        .ok()?
+        .modified()
+        .ok()?;
+    Some((cert, key))
+}
+
+/// Serves whichever certificate was loaded last.
+struct ReloadingResolver(RwLock<Arc<CertifiedKey>>);
+
+impl ResolvesServerCert for ReloadingResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.read().unwrap().clone())
+    }
+}
+
+/// Builds the rustls configuration and keeps polling the certificate and key
+/// files, swapping in the new pair whenever they change (e.g. when
+/// cert-manager rotates the secret).
+pub fn server_config(settings: TlsSettings) -> std::io::Result<ServerConfig> {
+    let resolver = Arc::new(ReloadingResolver(RwLock::new(Arc::new(
+        load_certified_key(&settings)?,
+    ))));
+
+    let watched = resolver.clone();
+    actix_web::rt::spawn(async move {
+        let mut last_modified = modified(&settings);
+        loop {
+            actix_web::rt::time::sleep(settings.reload_interval).await;
+            let current = modified(&settings);
+            if current == last_modified {
+                continue;
+            }
+            match load_certified_key(&settings) {
+                Ok(key) => {
+                    *watched.0.write().unwrap() = Arc::new(key);
+                    last_modified = current;
+                    tracing::info!("reloaded TLS certificate");
+                }
+                // The files may be mid-rotation; retry on the next tick.
+                Err(e) => tracing::warn!("failed to reload TLS certificate: {e}"),
+            }
+        }
+    });
+
+    Ok(ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver))
+}
+
+/// Middleware redirecting plain HTTP requests to HTTPS on `https_port`, when
+/// one is given.
+pub fn redirect_to_https<S, B>(
+    https_port: Option<u16>,
+    req: ServiceRequest,
+    srv: &S,
+) -> impl Future<Output = Result<ServiceResponse<actix_web::body::EitherBody<B>>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let redirect = match https_port {
+        Some(https_port)
+            if req.connection_info().scheme() == "http"
+                && !REDIRECT_EXEMPT.contains(&req.path()) =>
+        {
+            let host = req.connection_info().host().to_owned();
+            let host = host.split(':').next().unwrap_or_default().to_owned();
+            let port = match https_port {
+                443 => String::new(),
+                port => format!(":{port}"),
+            };
+            let target = match req.uri().path_and_query() {
+                Some(path) => format!("https://{host}{port}{path}"),
+                None => format!("https://{host}{port}/"),
+            };
+            Some(target)
+        }
+        _ => None,
+    };
+
+    let call = match redirect {
+        None => Ok(srv.call(req)),
+        Some(target) => Err((req, target)),
+    };
+    async move {
+        match call {
+            Ok(call) => Ok(call.await?.map_into_left_body()),
+            Err((req, target)) => Ok(req
+                .into_response(
+                    HttpResponse::PermanentRedirect()
+                        .insert_header((LOCATION, target))
+                        .finish(),
+                )
+                .map_into_right_body()),
+        }
+    }
+}
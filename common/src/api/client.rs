@@ -0,0 +1,235 @@
+//! Typed client for the backend REST API, usable from both the wasm frontend
+//! and native tools.
+
+use crate::api::routes;
+use crate::model::{
+    auth::{LoginRequest, LoginResponse, SessionInfo},
+    details::UserDetails,
+    query::UserQuery,
+    stats::FleetStats,
+    sync::{SyncDocument, SyncResult},
+    user::{
+        ApplyUserRequest, ApplyUserResponse, CreateUserRequest, User, UserMetadata, VmsVersion,
+    },
+    user_v2::UserV2,
+};
+use derive_more::Display;
+use serde::de::DeserializeOwned;
+
+#[derive(Debug, Display, Clone, PartialEq)]
+pub enum ClientError {
+    #[display(fmt = "{status}: {message}")]
+    Status {
+        status: u16,
+        message: String,
+        /// Seconds to wait before retrying, from `Retry-After`.
+        retry_after: Option<u64>,
+    },
+    #[display(fmt = "{_0}")]
+    Transport(String),
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Transport(error.to_string())
+    }
+}
+
+/// One page of a tenant list query.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    /// Number of tenants matching the query across all pages.
+    pub total: usize,
+}
+
+#[derive(Clone)]
+pub struct ApiClient {
+    base_url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    pub fn new(base_url: impl Into<String>) -> ApiClient {
+        ApiClient {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Sends `token` as a bearer credential with every request.
+    pub fn with_token(mut self, token: impl Into<String>) -> ApiClient {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn execute(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ClientError> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok());
+            Err(ClientError::Status {
+                status: status.as_u16(),
+                message: response.text().await.unwrap_or_default(),
+                retry_after,
+            })
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, ClientError> {
+        Ok(self.execute(request).await?.json().await?)
+    }
+
+    pub async fn login(&self, request: &LoginRequest) -> Result<LoginResponse, ClientError> {
+        self.send(self.http.post(self.url(routes::LOGIN)).json(request))
+            .await
+    }
+
+    /// Ends the session of the token this client was built with.
+    pub async fn logout(&self) -> Result<(), ClientError> {
+        self.execute(self.http.post(self.url(routes::LOGOUT)))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_session(&self) -> Result<SessionInfo, ClientError> {
+        self.send(self.http.get(self.url(routes::SESSION))).await
+    }
+
+    pub async fn get_users(&self) -> Result<Vec<User>, ClientError> {
+        self.send(self.http.get(self.url(routes::USERS))).await
+    }
+
+    pub async fn query_users(&self, query: &UserQuery) -> Result<UserPage, ClientError> {
+        let request = self.http.get(self.url(routes::USERS)).query(query);
+        let response = self.execute(request).await?;
+        let total = response
+            .headers()
+            .get(routes::TOTAL_COUNT_HEADER)
+            .and_then(|total| total.to_str().ok())
+            .and_then(|total| total.parse().ok());
+        let users: Vec<User> = response.json().await?;
+        Ok(UserPage {
+            total: total.unwrap_or(users.len()),
+            users,
+        })
+    }
+
+    pub async fn get_user(&self, user_global_id: &str) -> Result<User, ClientError> {
+        self.send(self.http.get(self.url(&routes::user(user_global_id))))
+            .await
+    }
+
+    pub async fn get_users_v2(&self) -> Result<Vec<UserV2>, ClientError> {
+        self.send(self.http.get(self.url(routes::USERS_V2))).await
+    }
+
+    pub async fn get_user_v2(&self, user_global_id: &str) -> Result<UserV2, ClientError> {
+        self.send(self.http.get(self.url(&routes::user_v2(user_global_id))))
+            .await
+    }
+
+    pub async fn get_user_details(&self, user_global_id: &str) -> Result<UserDetails, ClientError> {
+        self.send(
+            self.http
+                .get(self.url(&routes::user_details(user_global_id))),
+        )
+        .await
+    }
+
+    pub async fn create_user(&self, request: &CreateUserRequest) -> Result<User, ClientError> {
+        self.send(self.http.post(self.url(routes::USERS)).json(request))
+            .await
+    }
+
+    pub async fn apply_user(
+        &self,
+        user_global_id: &str,
+        request: &ApplyUserRequest,
+    ) -> Result<ApplyUserResponse, ClientError> {
+        self.send(
+            self.http
+                .put(self.url(&routes::user(user_global_id)))
+                .json(request),
+        )
+        .await
+    }
+
+    /// Upgrades to `vms_version`, or to the latest release when `None`.
+    pub async fn upgrade_user(
+        &self,
+        user_global_id: &str,
+        vms_version: Option<VmsVersion>,
+    ) -> Result<User, ClientError> {
+        let request = self.http.patch(self.url(&routes::user(user_global_id)));
+        let request = match vms_version {
+            Some(vms_version) => request.query(&[("vmsVersion", vms_version.to_string())]),
+            None => request,
+        };
+        self.send(request).await
+    }
+
+    /// Sets the given metadata fields, an empty string removing one, provided
+    /// the tenant still has the ETag `if_match` when one is given.
+    pub async fn update_user_metadata(
+        &self,
+        user_global_id: &str,
+        metadata: &UserMetadata,
+        if_match: Option<&str>,
+    ) -> Result<User, ClientError> {
+        let mut request = self
+            .http
+            .patch(self.url(&routes::user_metadata(user_global_id)))
+            .json(metadata);
+        if let Some(etag) = if_match {
+            request = request.header(reqwest::header::IF_MATCH, etag);
+        }
+        self.send(request).await
+    }
+
+    pub async fn delete_user(&self, user_global_id: &str) -> Result<User, ClientError> {
+        self.send(self.http.delete(self.url(&routes::user(user_global_id))))
+            .await
+    }
+
+    pub async fn get_stats(&self) -> Result<FleetStats, ClientError> {
+        self.send(self.http.get(self.url(routes::STATS))).await
+    }
+
+    pub async fn sync(
+        &self,
+        document: &SyncDocument,
+        dry_run: bool,
+        allow_delete: bool,
+    ) -> Result<SyncResult, ClientError> {
+        self.send(
+            self.http
+                .post(self.url(routes::SYNC))
+                .query(&[("dryRun", dry_run), ("noDelete", !allow_delete)])
+                .json(document),
+        )
+        .await
+    }
+}
@@ -0,0 +1,36 @@
+//! Paths of the backend REST API, shared by the server and its clients.
+
+pub const V1: &str = "/api/v1";
+pub const V2: &str = "/api/v2";
+
+pub const USERS: &str = "/api/v1/user";
+pub const USERS_V2: &str = "/api/v2/user";
+pub const SYNC: &str = "/api/v1/sync";
+pub const STATS: &str = "/api/stats";
+pub const LOGIN: &str = "/api/login";
+pub const LOGOUT: &str = "/api/logout";
+pub const SESSION: &str = "/api/session";
+pub const OPENAPI: &str = "/api/openapi.json";
+pub const DOCS: &str = "/api/docs";
+pub const DOCS_SCRIPT: &str = "/api/docs/swagger-ui-bundle.js";
+pub const DOCS_STYLE: &str = "/api/docs/swagger-ui.css";
+pub const LOG_LEVEL: &str = "/api/admin/log-level";
+
+/// Response header carrying the number of tenants matching a list query.
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+
+pub fn user(user_global_id: &str) -> String {
+    format!("{USERS}/{user_global_id}")
+}
+
+pub fn user_metadata(user_global_id: &str) -> String {
+    format!("{USERS}/{user_global_id}/metadata")
+}
+
+pub fn user_v2(user_global_id: &str) -> String {
+    format!("{USERS_V2}/{user_global_id}")
+}
+
+pub fn user_details(user_global_id: &str) -> String {
+    format!("{USERS_V2}/{user_global_id}/details")
+}
@@ -1 +1,2 @@
-pub mod model;
\ No newline at end of file
+pub mod api;
+pub mod model;
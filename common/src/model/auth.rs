@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginResponse {
+    /// Bearer token to send in the `Authorization` header.
+    pub token: String,
+    pub username: String,
+    /// Seconds until the session expires.
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionInfo {
+    /// Whether the backend requires signing in at all.
+    pub auth_enabled: bool,
+    /// Set when the request carried a valid session token.
+    pub username: Option<String>,
+    pub expires_in: Option<u64>,
+}
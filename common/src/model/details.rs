@@ -0,0 +1,44 @@
+use crate::model::user_v2::UserV2;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Everything known about a tenant's namespace, for the detail view.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserDetails {
+    pub user: UserV2,
+    pub uid: Option<String>,
+    /// Namespace `resourceVersion`, the tenant's ETag without quotes.
+    #[serde(default)]
+    pub resource_version: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+    pub resources: Vec<NamespaceResource>,
+    pub events: Vec<NamespaceEvent>,
+    /// Kinds, e.g. `Pod` or `Event`, that could not be listed and are
+    /// therefore missing from `resources` or `events`.
+    #[serde(default)]
+    pub unavailable: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NamespaceResource {
+    pub kind: String,
+    pub name: String,
+    /// Phase or readiness summary, e.g. `Running` or `2/3`.
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NamespaceEvent {
+    /// `Normal` or `Warning`.
+    pub event_type: String,
+    pub reason: String,
+    pub message: String,
+    /// `Kind/name` of the object the event is about.
+    pub object: String,
+    pub count: i32,
+    pub last_seen: Option<String>,
+}
@@ -1 +1,7 @@
+pub mod auth;
+pub mod details;
+pub mod query;
+pub mod stats;
+pub mod sync;
 pub mod user;
+pub mod user_v2;
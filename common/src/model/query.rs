@@ -0,0 +1,53 @@
+use crate::model::user::VmsVersion;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    #[default]
+    Name,
+    Version,
+    /// Namespace creation time.
+    Created,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Query parameters of the tenant list. Without `page` every matching tenant
+/// is returned; the number of matches is reported in `X-Total-Count`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuery {
+    /// Case-insensitive substring of the tenant or display name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vms_version: Option<VmsVersion>,
+    /// Only tenants created at or after this RFC 3339 timestamp or
+    /// `YYYY-MM-DD` date (UTC).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<String>,
+    /// Only tenants created before this RFC 3339 timestamp or `YYYY-MM-DD`
+    /// date (UTC).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortField>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    /// 1-based page number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<usize>,
+}
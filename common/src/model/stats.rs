@@ -0,0 +1,33 @@
+use crate::model::user::VmsVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Aggregate view of all tenants, served by `/api/stats`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FleetStats {
+    pub total: usize,
+    /// Tenant count per version, every known version included.
+    pub by_vms_version: BTreeMap<String, usize>,
+    pub by_server_type: BTreeMap<String, usize>,
+    /// Versions older than this one are out of support.
+    pub min_supported_version: VmsVersion,
+    /// Names of tenants on unsupported versions.
+    pub unsupported: Vec<String>,
+    /// Names of tenants whose namespace is not `Active` or that have a
+    /// Deployment or StatefulSet short of ready replicas.
+    pub not_ready: Vec<String>,
+    /// Most recent mutating API calls, newest first.
+    pub recent_operations: Vec<Operation>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Operation {
+    /// RFC 3339 time the call completed.
+    pub at: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub request_id: Option<String>,
+}
@@ -0,0 +1,183 @@
+use crate::model::user::{User, VmsVersion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DesiredUser {
+    pub user_name: String,
+    pub vms_version: VmsVersion,
+    pub server_type: Option<String>,
+}
+
+impl DesiredUser {
+    pub fn to_user(&self) -> User {
+        let mut user = User::new(self.user_name.clone(), self.vms_version);
+        if let Some(server_type) = &self.server_type {
+            user.server_type = server_type.clone();
+        }
+        user
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncDocument {
+    pub users: Vec<DesiredUser>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncPlan {
+    pub create: Vec<User>,
+    pub upgrade: Vec<User>,
+    /// Tenants the document pins to an older release than they run. They are
+    /// reported but never applied.
+    #[serde(default)]
+    pub downgrade: Vec<User>,
+    pub delete: Vec<User>,
+}
+
+impl SyncPlan {
+    /// Computes the operations needed to turn `current` into `desired`.
+    /// Tenants missing from `desired` are only scheduled for deletion when
+    /// `allow_delete` is set, and downgrades are set aside in `downgrade`.
+    pub fn compute(desired: &SyncDocument, current: &[User], allow_delete: bool) -> SyncPlan {
+        let mut plan = SyncPlan::default();
+
+        for desired_user in &desired.users {
+            let user = desired_user.to_user();
+            match current
+                .iter()
+                .find(|c| c.get_global_id() == user.get_global_id())
+            {
+                None => plan.create.push(user),
+                Some(existing) if user.vms_version < existing.vms_version => {
+                    plan.downgrade.push(user)
+                }
+                Some(existing)
+                    if existing.vms_version != user.vms_version
+                        || existing.server_type != user.server_type =>
+                {
+                    plan.upgrade.push(user)
+                }
+                Some(_) => {}
+            }
+        }
+
+        if allow_delete {
+            for existing in current {
+                if !desired
+                    .users
+                    .iter()
+                    .any(|d| d.to_user().get_global_id() == existing.get_global_id())
+                {
+                    plan.delete.push(existing.clone());
+                }
+            }
+        }
+
+        plan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty()
+            && self.upgrade.is_empty()
+            && self.downgrade.is_empty()
+            && self.delete.is_empty()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncResult {
+    pub plan: SyncPlan,
+    pub dry_run: bool,
+    pub failed: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DesiredUser, SyncDocument, SyncPlan};
+    use crate::model::user::{User, VmsVersion};
+
+    fn desired(user_name: &str, vms_version: VmsVersion) -> DesiredUser {
+        DesiredUser {
+            user_name: user_name.to_owned(),
+            vms_version,
+            server_type: None,
+        }
+    }
+
+    fn document(users: Vec<DesiredUser>) -> SyncDocument {
+        SyncDocument { users }
+    }
+
+    #[test]
+    fn missing_tenants_are_created() {
+        let plan = SyncPlan::compute(
+            &document(vec![desired("alice", VmsVersion::XProtect2023R3)]),
+            &[],
+            true,
+        );
+        assert_eq!(
+            plan.create,
+            vec![User::new("alice".to_owned(), VmsVersion::XProtect2023R3)]
+        );
+        assert!(plan.upgrade.is_empty() && plan.downgrade.is_empty() && plan.delete.is_empty());
+    }
+
+    #[test]
+    fn newer_versions_are_upgrades() {
+        let current = [User::new("alice".to_owned(), VmsVersion::XProtect2023R3)];
+        let plan = SyncPlan::compute(
+            &document(vec![desired("alice", VmsVersion::XProtect2024R1)]),
+            &current,
+            true,
+        );
+        assert_eq!(
+            plan.upgrade,
+            vec![User::new("alice".to_owned(), VmsVersion::XProtect2024R1)]
+        );
+        assert!(plan.create.is_empty() && plan.downgrade.is_empty() && plan.delete.is_empty());
+    }
+
+    #[test]
+    fn older_versions_are_not_upgrades() {
+        let current = [User::new("alice".to_owned(), VmsVersion::XProtect2024R1)];
+        let plan = SyncPlan::compute(
+            &document(vec![desired("alice", VmsVersion::XProtect2023R3)]),
+            &current,
+            true,
+        );
+        assert!(plan.upgrade.is_empty());
+        assert_eq!(
+            plan.downgrade,
+            vec![User::new("alice".to_owned(), VmsVersion::XProtect2023R3)]
+        );
+    }
+
+    #[test]
+    fn matching_tenants_need_nothing() {
+        let current = [User::new("alice".to_owned(), VmsVersion::XProtect2024R1)];
+        let plan = SyncPlan::compute(
+            &document(vec![desired("alice", VmsVersion::XProtect2024R1)]),
+            &current,
+            true,
+        );
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn unlisted_tenants_are_deleted() {
+        let current = [User::new("bob".to_owned(), VmsVersion::XProtect2024R1)];
+        let plan = SyncPlan::compute(&document(Vec::new()), &current, true);
+        assert_eq!(plan.delete, current);
+    }
+
+    #[test]
+    fn unlisted_tenants_are_kept_without_allow_delete() {
+        let current = [User::new("bob".to_owned(), VmsVersion::XProtect2024R1)];
+        let plan = SyncPlan::compute(&document(Vec::new()), &current, false);
+        assert!(plan.is_empty());
+    }
+}
@@ -1,8 +1,23 @@
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, EnumString};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
 use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, EnumString, Display, Eq, PartialEq, Clone, Copy, Debug)]
+#[derive(
+    Deserialize,
+    Serialize,
+    EnumString,
+    EnumIter,
+    Display,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Debug,
+)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum VmsVersion {
     XProtect2023R1,
     XProtect2023R2,
@@ -10,11 +25,55 @@ pub enum VmsVersion {
     XProtect2024R1,
 }
 
+impl VmsVersion {
+    pub fn all() -> Vec<VmsVersion> {
+        VmsVersion::iter().collect()
+    }
+
+    /// The newest release; variants are declared oldest first.
+    pub fn latest() -> VmsVersion {
+        VmsVersion::iter().next_back().unwrap()
+    }
+
+    /// Releases a tenant on this version can be upgraded to, oldest first.
+    pub fn newer_versions(self) -> Vec<VmsVersion> {
+        VmsVersion::iter()
+            .filter(|version| *version > self)
+            .collect()
+    }
+}
+
+/// Descriptive tenant information, stored as namespace annotations.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_center: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct User {
     pub user_name: String,
     pub vms_version: VmsVersion,
     pub server_type: String,
+    #[serde(default)]
+    pub metadata: UserMetadata,
+    /// Namespace creation time, RFC 3339.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Time of the most recent change to the namespace, RFC 3339.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Namespace UID, which unlike the name is never reused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
 }
 
 impl User {
@@ -23,6 +82,10 @@ impl User {
             user_name,
             vms_version,
             server_type: "vms".to_owned(),
+            metadata: UserMetadata::default(),
+            created_at: None,
+            updated_at: None,
+            uid: None,
         }
     }
 
@@ -30,3 +93,33 @@ impl User {
         format!("{}", self.user_name.to_lowercase())
     }
 }
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateUserRequest {
+    pub user_name: String,
+    pub vms_version: VmsVersion,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApplyUserRequest {
+    pub vms_version: VmsVersion,
+    pub server_type: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyOutcome {
+    Created,
+    Changed,
+    Unchanged,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApplyUserResponse {
+    pub user: User,
+    pub result: ApplyOutcome,
+}
@@ -0,0 +1,48 @@
+use crate::model::user::{ApplyOutcome, User, UserMetadata, VmsVersion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum UserStatus {
+    Active,
+    Terminating,
+    Unknown,
+}
+
+/// Tenant representation of `/api/v2`. Timestamps are RFC 3339 strings.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UserV2 {
+    pub user_name: String,
+    pub vms_version: VmsVersion,
+    pub server_type: String,
+    pub status: UserStatus,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Contact responsible for the tenant, i.e. `metadata.owner_email`.
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub metadata: UserMetadata,
+    pub uid: Option<String>,
+}
+
+impl From<UserV2> for User {
+    fn from(user: UserV2) -> Self {
+        User {
+            user_name: user.user_name,
+            vms_version: user.vms_version,
+            server_type: user.server_type,
+            metadata: user.metadata,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            uid: user.uid,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApplyUserResponseV2 {
+    pub user: UserV2,
+    pub result: ApplyOutcome,
+}
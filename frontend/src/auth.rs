@@ -0,0 +1,240 @@
+use common::api::client::ClientError;
+use common::model::auth::{LoginRequest, LoginResponse};
+use patternfly_yew::prelude::*;
+use serde::Deserialize;
+use yew::prelude::*;
+use yew_hooks::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api_client, error_message, Route};
+
+const TOKEN_KEY: &str = "kitodar.token";
+const USERNAME_KEY: &str = "kitodar.username";
+const EXPIRES_AT_KEY: &str = "kitodar.expiresAt";
+
+/// The session kept in local storage across page loads.
+#[derive(Clone, PartialEq)]
+pub struct StoredSession {
+    pub token: String,
+    pub username: String,
+    /// Milliseconds since the epoch, as returned by `Date.now()`.
+    pub expires_at: f64,
+}
+
+impl StoredSession {
+    pub fn is_expired(&self) -> bool {
+        js_sys::Date::now() >= self.expires_at
+    }
+}
+
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+pub fn stored_session() -> Option<StoredSession> {
+    let storage = storage()?;
+    Some(StoredSession {
+        token: storage.get_item(TOKEN_KEY).ok()??,
+        username: storage.get_item(USERNAME_KEY).ok()??,
+        expires_at: storage.get_item(EXPIRES_AT_KEY).ok()??.parse().ok()?,
+    })
+}
+
+fn store_session(response: &LoginResponse) {
+    if let Some(storage) = storage() {
+        let expires_at = js_sys::Date::now() + response.expires_in as f64 * 1000.0;
+        let _ = storage.set_item(TOKEN_KEY, &response.token);
+        let _ = storage.set_item(USERNAME_KEY, &response.username);
+        let _ = storage.set_item(EXPIRES_AT_KEY, &expires_at.to_string());
+    }
+}
+
+fn clear_session() {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(TOKEN_KEY);
+        let _ = storage.remove_item(USERNAME_KEY);
+        let _ = storage.remove_item(EXPIRES_AT_KEY);
+    }
+}
+
+/// Drops the session and sends the user to the login page, telling them why
+/// if they had been signed in.
+fn expire_session() {
+    let login = if stored_session().is_some() {
+        "/login?expired=true"
+    } else {
+        "/login"
+    };
+    clear_session();
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().set_href(login);
+    }
+}
+
+/// Passes `result` through, signing the user out when the backend rejected
+/// the session.
+pub fn check_session<T>(result: Result<T, ClientError>) -> Result<T, ClientError> {
+    if let Err(ClientError::Status { status: 401, .. }) = &result {
+        expire_session();
+    }
+    result
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RequireSessionProps {
+    pub children: Html,
+}
+
+/// Renders its children only when the backend accepts the stored session, or
+/// does not require one.
+#[function_component(RequireSession)]
+pub fn require_session(props: &RequireSessionProps) -> Html {
+    let allowed = use_state_eq(|| false);
+    {
+        let allowed = allowed.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match api_client().get_session().await {
+                    Ok(session) if session.auth_enabled && session.username.is_none() => {
+                        expire_session()
+                    }
+                    // Let the page itself report an unreachable backend.
+                    _ => allowed.set(true),
+                }
+            });
+            || ()
+        });
+    }
+    use_interval(
+        || {
+            if stored_session().is_some_and(|session| session.is_expired()) {
+                expire_session();
+            }
+        },
+        30000,
+    );
+
+    if *allowed {
+        props.children.clone()
+    } else {
+        html! {}
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, Default)]
+struct LoginQuery {
+    #[serde(default)]
+    expired: bool,
+}
+
+#[function_component(LoginPage)]
+pub fn login_page() -> Html {
+    let navigator = use_navigator();
+    let expired = use_location()
+        .and_then(|location| location.query::<LoginQuery>().ok())
+        .unwrap_or_default()
+        .expired;
+    let username = use_state_eq(String::new);
+    let password = use_state_eq(String::new);
+    let error = use_state_eq(|| None::<String>);
+
+    let onchangeusername = {
+        let username = username.clone();
+        Callback::from(move |value| username.set(value))
+    };
+    let onchangepassword = {
+        let password = password.clone();
+        Callback::from(move |value| password.set(value))
+    };
+
+    let onsubmit = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let request = LoginRequest {
+                username: (*username).clone(),
+                password: (*password).clone(),
+            };
+            let navigator = navigator.clone();
+            let password = password.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match api_client().login(&request).await {
+                    Ok(response) => {
+                        store_session(&response);
+                        if let Some(navigator) = navigator {
+                            navigator.push(&Route::Home);
+                        }
+                    }
+                    Err(ClientError::Status { status: 401, .. }) => {
+                        password.set(String::new());
+                        error.set(Some("Invalid username or password".to_string()));
+                    }
+                    Err(e) => error.set(Some(error_message(&e))),
+                }
+            });
+        })
+    };
+
+    let title = html_nested! {<Title size={Size::XXLarge}>{"Sign in to KITODAR"}</Title>};
+    let notice = match (&*error, expired) {
+        (Some(error), _) => {
+            html! { <Alert inline=true r#type={AlertType::Danger} title={error.clone()} /> }
+        }
+        (None, true) => {
+            html! { <Alert inline=true r#type={AlertType::Info} title="Your session has expired, please sign in again" /> }
+        }
+        (None, false) => html! {},
+    };
+
+    html! {
+        <>
+            <Background/>
+            <Login>
+                <LoginMain>
+                    <LoginMainHeader
+                        {title}
+                        description="Sign in with your KITODAR account."
+                    />
+                    <LoginMainBody>
+                        {notice}
+                        <Form {onsubmit} method="dialog">
+                            <FormGroup label="Username">
+                                <TextInput required=true name="username" onchange={onchangeusername} value={(*username).clone()} />
+                            </FormGroup>
+                            <FormGroup label="Password">
+                                <TextInput required=true name="password" r#type={TextInputType::Password} onchange={onchangepassword} value={(*password).clone()} />
+                            </FormGroup>
+                            <ActionGroup>
+                                <Button label="Sign in" r#type={ButtonType::Submit} variant={ButtonVariant::Primary}/>
+                            </ActionGroup>
+                        </Form>
+                    </LoginMainBody>
+                </LoginMain>
+            </Login>
+        </>
+    }
+}
+
+/// Shows the signed-in user and ends their session.
+#[function_component(LogoutButton)]
+pub fn logout_button() -> Html {
+    let Some(session) = stored_session() else {
+        return html! {};
+    };
+    let onclick = Callback::from(|_| {
+        wasm_bindgen_futures::spawn_local(async move {
+            let _ = api_client().logout().await;
+            clear_session();
+            if let Some(window) = web_sys::window() {
+                let _ = window.location().set_href("/login");
+            }
+        });
+    });
+    html! {
+        <Button variant={ButtonVariant::Link} {onclick}>
+            {format!("Sign out {}", session.username)}
+        </Button>
+    }
+}
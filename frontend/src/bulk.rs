@@ -0,0 +1,333 @@
+use std::{collections::BTreeMap, rc::Rc, time::Duration};
+
+use common::{
+    api::client::ClientError,
+    model::user::{User, VmsVersion},
+};
+use patternfly_yew::prelude::*;
+use yew::{platform::time::sleep, prelude::*};
+
+use crate::{delete_user, error_message, upgrade_user};
+
+/// Tenants ticked in the table, keyed by name so the selection survives
+/// paging and reloads.
+#[derive(Clone, PartialEq)]
+pub struct Selection {
+    pub selected: Rc<BTreeMap<String, User>>,
+    pub ontoggle: Callback<(User, bool)>,
+}
+
+#[function_component(SelectRow)]
+pub fn select_row() -> Html {
+    let user = use_context::<User>().expect("no ctx found");
+    let selection = use_context::<Selection>().expect("no ctx found");
+
+    let checked = selection.selected.contains_key(&user.user_name);
+    let onchange = {
+        let user = user.clone();
+        selection
+            .ontoggle
+            .reform(move |_: Event| (user.clone(), !checked))
+    };
+
+    html! {
+        <input type="checkbox" class="pf-v5-c-check__input" aria-label={format!("Select {}", user.user_name)} {checked} {onchange} />
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BulkAction {
+    Upgrade(VmsVersion),
+    Delete,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum OutcomeState {
+    Pending,
+    Done,
+    Skipped(String),
+    Failed(String),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Outcome {
+    pub user_name: String,
+    pub state: OutcomeState,
+}
+
+/// How often one tenant's operation is retried after being rate limited.
+const MAX_RATE_LIMIT_RETRIES: usize = 10;
+
+/// Runs `action` on `user`, waiting out the backend's `Retry-After` whenever
+/// the mutation rate limit answers 429.
+async fn run_one(action: BulkAction, user: &User) -> Result<(), ClientError> {
+    let mut retries = 0;
+    loop {
+        let result = match action {
+            BulkAction::Upgrade(target) => upgrade_user(&user.user_name, target).await.map(drop),
+            BulkAction::Delete => delete_user(&user.user_name).await.map(drop),
+        };
+        match result {
+            Err(ClientError::Status {
+                status: 429,
+                retry_after,
+                ..
+            }) if retries < MAX_RATE_LIMIT_RETRIES => {
+                retries += 1;
+                sleep(Duration::from_secs(retry_after.unwrap_or(1).max(1))).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Runs `action` on one tenant at a time, reporting each result as it
+/// arrives. Large selections exceed the per-client rate limit, so throttled
+/// operations are retried once the backend allows it.
+async fn run_bulk(action: BulkAction, users: Vec<User>, progress: UseStateHandle<Vec<Outcome>>) {
+    let mut outcomes: Vec<Outcome> = users
+        .iter()
+        .map(|user| Outcome {
+            user_name: user.user_name.clone(),
+            state: OutcomeState::Pending,
+        })
+        .collect();
+    progress.set(outcomes.clone());
+
+    for (index, user) in users.into_iter().enumerate() {
+        let state = match action {
+            BulkAction::Upgrade(target) if user.vms_version >= target => {
+                OutcomeState::Skipped(format!("already on {}", user.vms_version))
+            }
+            _ => match run_one(action, &user).await {
+                Ok(()) => OutcomeState::Done,
+                Err(e) => OutcomeState::Failed(error_message(&e)),
+            },
+        };
+        outcomes[index].state = state;
+        progress.set(outcomes.clone());
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct BulkToolbarProps {
+    pub selected: Rc<BTreeMap<String, User>>,
+    pub onclear: Callback<()>,
+    /// Emitted once every operation has finished.
+    pub ondone: Callback<()>,
+}
+
+#[function_component(BulkToolbar)]
+pub fn bulk_toolbar(props: &BulkToolbarProps) -> Html {
+    let backdropper = use_backdrop();
+    let target = use_state_eq(|| None::<VmsVersion>);
+    let running = use_state_eq(|| None::<BulkAction>);
+    let progress = use_state_eq(Vec::<Outcome>::new);
+
+    let start = {
+        let selected = props.selected.clone();
+        let ondone = props.ondone.clone();
+        let running = running.clone();
+        let progress = progress.clone();
+        Callback::from(move |action: BulkAction| {
+            let users: Vec<User> = selected.values().cloned().collect();
+            let ondone = ondone.clone();
+            let running = running.clone();
+            let progress = progress.clone();
+            running.set(Some(action));
+            wasm_bindgen_futures::spawn_local(async move {
+                run_bulk(action, users, progress).await;
+                running.set(None);
+                ondone.emit(());
+            });
+        })
+    };
+
+    let onselect = {
+        let target = target.clone();
+        Callback::from(move |version: VmsVersion| target.set(Some(version)))
+    };
+    let onupgrade = {
+        let backdropper = backdropper.clone();
+        let start = start.clone();
+        let selected = props.selected.clone();
+        let target = *target;
+        Callback::from(move |_| {
+            if let (Some(backdropper), Some(target)) = (&backdropper, target) {
+                backdropper.open(html!(
+                    <BulkConfirmDialog action={BulkAction::Upgrade(target)} users={selected.values().cloned().collect::<Vec<_>>()} onconfirm={start.reform(move |_| BulkAction::Upgrade(target))} />
+                ));
+            }
+        })
+    };
+    let ondelete = {
+        let selected = props.selected.clone();
+        Callback::from(move |_| {
+            if let Some(backdropper) = &backdropper {
+                backdropper.open(html!(
+                    <BulkConfirmDialog action={BulkAction::Delete} users={selected.values().cloned().collect::<Vec<_>>()} onconfirm={start.reform(|_| BulkAction::Delete)} />
+                ));
+            }
+        })
+    };
+    let ondismiss = {
+        let progress = progress.clone();
+        Callback::from(move |_| progress.set(Vec::new()))
+    };
+
+    let busy = running.is_some();
+    let count = props.selected.len();
+    let toolbar = if count > 0 || busy {
+        html! {
+            <Toolbar>
+                <ToolbarContent>
+                    <ToolbarItem>{format!("{count} selected")}</ToolbarItem>
+                    <ToolbarItem>
+                        <SimpleSelect<VmsVersion>
+                            placeholder="Upgrade to version"
+                            selected={*target}
+                            entries={VmsVersion::all()}
+                            {onselect}
+                        />
+                    </ToolbarItem>
+                    <ToolbarItem>
+                        <Button variant={ButtonVariant::Primary} disabled={busy || target.is_none() || count == 0} onclick={onupgrade}>{"Upgrade selected"}</Button>
+                    </ToolbarItem>
+                    <ToolbarItem>
+                        <Button variant={ButtonVariant::Danger} disabled={busy || count == 0} onclick={ondelete}>{"Delete selected"}</Button>
+                    </ToolbarItem>
+                    <ToolbarItem>
+                        <Button variant={ButtonVariant::Link} disabled={busy} onclick={props.onclear.reform(|_| ())}>{"Clear selection"}</Button>
+                    </ToolbarItem>
+                </ToolbarContent>
+            </Toolbar>
+        }
+    } else {
+        html!()
+    };
+
+    html! {<>
+        {toolbar}
+        {progress_panel(&progress, busy, ondismiss)}
+    </>}
+}
+
+fn progress_panel(outcomes: &[Outcome], busy: bool, ondismiss: Callback<MouseEvent>) -> Html {
+    if outcomes.is_empty() {
+        return html!();
+    }
+    let finished = outcomes
+        .iter()
+        .filter(|outcome| outcome.state != OutcomeState::Pending)
+        .count();
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.state, OutcomeState::Failed(_)))
+        .count();
+    let alert_type = match (busy, failed) {
+        (true, _) => AlertType::Info,
+        (false, 0) => AlertType::Success,
+        (false, _) => AlertType::Danger,
+    };
+
+    html! {
+        <Alert inline=true r#type={alert_type} title={format!("{finished}/{} finished, {failed} failed", outcomes.len())}>
+            <ul>
+                { for outcomes.iter().map(|outcome| {
+                    let state = match &outcome.state {
+                        OutcomeState::Pending => html!(<Spinner size={SpinnerSize::Md} />),
+                        OutcomeState::Done => html!({"done"}),
+                        OutcomeState::Skipped(reason) => html!({format!("skipped, {reason}")}),
+                        OutcomeState::Failed(reason) => html!(<b>{format!("failed: {reason}")}</b>),
+                    };
+                    html! { <li>{&outcome.user_name}{": "}{state}</li> }
+                }) }
+            </ul>
+            if !busy {
+                <Button variant={ButtonVariant::Link} onclick={ondismiss}>{"Dismiss"}</Button>
+            }
+        </Alert>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct BulkConfirmDialogProps {
+    pub action: BulkAction,
+    pub users: Vec<User>,
+    pub onconfirm: Callback<()>,
+}
+
+/// Lists what a bulk action will do; deleting requires typing the number of
+/// tenants affected.
+#[function_component(BulkConfirmDialog)]
+fn bulk_confirm_dialog(props: &BulkConfirmDialogProps) -> Html {
+    let backdropper = use_backdrop();
+    let typed = use_state_eq(String::new);
+
+    let onchange = {
+        let typed = typed.clone();
+        Callback::from(move |value| typed.set(value))
+    };
+    let onclose = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper.close();
+        }
+    });
+    let onok = {
+        let onconfirm = props.onconfirm.clone();
+        let onclose = onclose.clone();
+        Callback::from(move |_| {
+            onclose.emit(());
+            onconfirm.emit(());
+        })
+    };
+
+    let count = props.users.len();
+    let (title, confirm, body) = match props.action {
+        BulkAction::Upgrade(target) => (
+            format!("Upgrade {count} tenants to {target}?"),
+            html! {
+                <Button variant={ButtonVariant::Primary} onclick={onok}>{"Upgrade"}</Button>
+            },
+            html! {
+                <ul>
+                    { for props.users.iter().map(|user| html! {
+                        <li>
+                            {format!("{}: {} \u{2192} {}", user.user_name, user.vms_version, target)}
+                            if user.vms_version >= target {
+                                {" (skipped)"}
+                            }
+                        </li>
+                    }) }
+                </ul>
+            },
+        ),
+        BulkAction::Delete => (
+            format!("Delete {count} tenants?"),
+            html! {
+                <Button variant={ButtonVariant::Danger} disabled={*typed != count.to_string()} onclick={onok}>{"Delete"}</Button>
+            },
+            html! {<>
+                <ul>
+                    { for props.users.iter().map(|user| html! { <li>{&user.user_name}</li> }) }
+                </ul>
+                <Form>
+                    <FormGroup label={format!("Type {count} to confirm")}>
+                        <TextInput {onchange} value={(*typed).clone()} />
+                    </FormGroup>
+                </Form>
+            </>},
+        ),
+    };
+
+    let footer = html! {<>
+        {confirm}
+        <Button variant={ButtonVariant::Link} onclick={onclose.reform(|_| ())}>{"Cancel"}</Button>
+    </>};
+
+    html! {
+        <Modal {title} variant={ModalVariant::Small} {onclose} {footer}>
+            {body}
+        </Modal>
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use common::model::stats::{FleetStats, Operation};
+use patternfly_yew::prelude::*;
+use yew::prelude::*;
+use yew_hooks::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api_client, auth::check_session, error_message, Route};
+
+#[function_component(Dashboard)]
+pub fn dashboard() -> Html {
+    let reload = use_state_eq(|| 0);
+    let stats = use_state(|| None);
+    {
+        let stats = stats.clone();
+        use_effect_with(*reload, move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                stats.set(Some(check_session(api_client().get_stats().await)));
+            });
+            || ()
+        });
+    }
+    {
+        let reload = reload.clone();
+        use_interval(
+            move || {
+                reload.set(*reload + 1);
+            },
+            15000,
+        );
+    }
+
+    let content = match &*stats {
+        None => html! { <div>{"Loading..."}</div> },
+        Some(Err(error)) => html! {
+            <Alert inline=true r#type={AlertType::Danger} title={format!("Could not load statistics: {}", error_message(error))} />
+        },
+        Some(Ok(stats)) => stats_view(stats),
+    };
+
+    html! {
+        <>
+            <Background/>
+            <PageSection>
+                <Link<Route> to={Route::Home}>{"\u{2190} All users"}</Link<Route>>
+                <Title size={Size::XXLarge}>{"Fleet overview"}</Title>
+                {content}
+            </PageSection>
+        </>
+    }
+}
+
+fn stats_view(stats: &FleetStats) -> Html {
+    html! {
+        <>
+            <DescriptionList>
+                <DescriptionGroup term="Tenants">{stats.total}</DescriptionGroup>
+                <DescriptionGroup term={format!("Older than {}", stats.min_supported_version)}>
+                    {stats.unsupported.len()}
+                </DescriptionGroup>
+                <DescriptionGroup term="Not ready">{stats.not_ready.len()}</DescriptionGroup>
+            </DescriptionList>
+            if !stats.unsupported.is_empty() {
+                <Alert inline=true r#type={AlertType::Warning} title="Tenants on unsupported versions">
+                    {tenant_links(&stats.unsupported)}
+                </Alert>
+            }
+            if !stats.not_ready.is_empty() {
+                <Alert inline=true r#type={AlertType::Danger} title="Tenants not ready">
+                    {tenant_links(&stats.not_ready)}
+                </Alert>
+            }
+            <Title size={Size::Large}>{"Tenants by VMS version"}</Title>
+            {bar_chart(&stats.by_vms_version, stats.total)}
+            <Title size={Size::Large}>{"Tenants by server type"}</Title>
+            {bar_chart(&stats.by_server_type, stats.total)}
+            <Title size={Size::Large}>{"Recent operations"}</Title>
+            {operations(&stats.recent_operations)}
+        </>
+    }
+}
+
+fn tenant_links(names: &[String]) -> Html {
+    html! {
+        <ul>
+            { for names.iter().map(|name| html! {
+                <li><Link<Route> to={Route::User { id: name.clone() }}>{name}</Link<Route>></li>
+            }) }
+        </ul>
+    }
+}
+
+/// Horizontal bars scaled to `total`.
+fn bar_chart(counts: &BTreeMap<String, usize>, total: usize) -> Html {
+    let total = total.max(1);
+    html! {
+        <table class="pf-v5-c-table pf-m-compact">
+            <tbody>
+                { for counts.iter().map(|(label, count)| html! {
+                    <tr>
+                        <td style="width: 25%">{label}</td>
+                        <td>
+                            <div style={format!(
+                                "width: {}%; min-width: 2px; height: 1em; background: var(--pf-v5-global--primary-color--100)",
+                                count * 100 / total
+                            )} />
+                        </td>
+                        <td style="width: 10%">{count}</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
+
+fn operations(operations: &[Operation]) -> Html {
+    if operations.is_empty() {
+        return html! { <p>{"No operations since the backend started."}</p> };
+    }
+    html! {
+        <table class="pf-v5-c-table pf-m-compact">
+            <thead>
+                <tr><th>{"Time"}</th><th>{"Request"}</th><th>{"Status"}</th><th>{"Request ID"}</th></tr>
+            </thead>
+            <tbody>
+                { for operations.iter().map(|operation| html! {
+                    <tr>
+                        <td>{&operation.at}</td>
+                        <td>{format!("{} {}", operation.method, operation.path)}</td>
+                        <td>{operation.status}</td>
+                        <td><code>{operation.request_id.clone().unwrap_or_default()}</code></td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use common::model::details::{NamespaceEvent, NamespaceResource, UserDetails};
+use patternfly_yew::prelude::*;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::{api_client, auth::check_session, error_message, metadata::MetadataDialog, Route};
+
+#[derive(Properties, PartialEq)]
+pub struct UserDetailsProps {
+    pub id: String,
+}
+
+#[function_component(UserDetailsPage)]
+pub fn user_details_page(props: &UserDetailsProps) -> Html {
+    let details = use_state(|| None);
+    let reload = use_state_eq(|| 0);
+    {
+        let details = details.clone();
+        use_effect_with((props.id.clone(), *reload), move |(id, _)| {
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                details.set(Some(check_session(
+                    api_client().get_user_details(&id).await,
+                )));
+            });
+            || ()
+        });
+    }
+
+    let content = match &*details {
+        None => html! { <div>{"Loading..."}</div> },
+        Some(Err(error)) => html! {
+            <Alert inline=true r#type={AlertType::Danger} title={format!("Could not load {}: {}", props.id, error_message(error))} />
+        },
+        Some(Ok(details)) => details_view(details),
+    };
+
+    let backdropper = use_backdrop();
+    let edit = match &*details {
+        Some(Ok(details)) => {
+            let user_name = details.user.user_name.clone();
+            let metadata = details.user.metadata.clone();
+            let etag = details
+                .resource_version
+                .as_ref()
+                .map(|resource_version| format!("\"{resource_version}\""));
+            let onsaved = {
+                let reload = reload.clone();
+                Callback::from(move |_| reload.set(*reload + 1))
+            };
+            let onclick = Callback::from(move |_| {
+                if let Some(backdropper) = &backdropper {
+                    backdropper.open(html!(<MetadataDialog user_name={user_name.clone()} metadata={metadata.clone()} etag={etag.clone()} onsaved={onsaved.clone()} />));
+                }
+            });
+            html! { <Button variant={ButtonVariant::Secondary} {onclick}>{"Edit metadata"}</Button> }
+        }
+        _ => html! {},
+    };
+
+    html! {
+        <>
+            <Background/>
+            <PageSection>
+                <Link<Route> to={Route::Home}>{"\u{2190} All users"}</Link<Route>>
+                <Title size={Size::XXLarge}>{&props.id}</Title>
+                {edit}
+                {content}
+            </PageSection>
+        </>
+    }
+}
+
+fn details_view(details: &UserDetails) -> Html {
+    let user = &details.user;
+    let or_unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+    html! {
+        <>
+            <DescriptionList>
+                <DescriptionGroup term="Status">{format!("{:?}", user.status)}</DescriptionGroup>
+                <DescriptionGroup term="VMS Version">{user.vms_version.to_string()}</DescriptionGroup>
+                <DescriptionGroup term="Server Type">{&user.server_type}</DescriptionGroup>
+                <DescriptionGroup term="Created">{or_unknown(&user.created_at)}</DescriptionGroup>
+                <DescriptionGroup term="Last Modified">{or_unknown(&user.updated_at)}</DescriptionGroup>
+                <DescriptionGroup term="UID">{or_unknown(&details.uid)}</DescriptionGroup>
+            </DescriptionList>
+            <Title size={Size::Large}>{"Metadata"}</Title>
+            <DescriptionList>
+                <DescriptionGroup term="Display Name">{or_unknown(&user.metadata.display_name)}</DescriptionGroup>
+                <DescriptionGroup term="Owner Email">{or_unknown(&user.metadata.owner_email)}</DescriptionGroup>
+                <DescriptionGroup term="Cost Center">{or_unknown(&user.metadata.cost_center)}</DescriptionGroup>
+                <DescriptionGroup term="Notes">{or_unknown(&user.metadata.notes)}</DescriptionGroup>
+            </DescriptionList>
+            <Title size={Size::Large}>{"Labels"}</Title>
+            {key_values(&details.labels)}
+            <Title size={Size::Large}>{"Annotations"}</Title>
+            {key_values(&details.annotations)}
+            <Title size={Size::Large}>{"Resources"}</Title>
+            {unavailable(details.unavailable.iter().filter(|kind| *kind != "Event"))}
+            {resources(&details.resources)}
+            <Title size={Size::Large}>{"Recent Events"}</Title>
+            {unavailable(details.unavailable.iter().filter(|kind| *kind == "Event"))}
+            {events(&details.events)}
+        </>
+    }
+}
+
+fn key_values(entries: &BTreeMap<String, String>) -> Html {
+    if entries.is_empty() {
+        return html! { <p>{"None"}</p> };
+    }
+    html! {
+        <table class="pf-v5-c-table pf-m-compact">
+            <tbody>
+                { for entries.iter().map(|(key, value)| html! {
+                    <tr><td><code>{key}</code></td><td>{value}</td></tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
+
+/// Warns that some kinds could not be listed, typically for lack of RBAC
+/// permissions, so the section below may be incomplete.
+fn unavailable<'a>(kinds: impl Iterator<Item = &'a String>) -> Html {
+    let kinds: Vec<&str> = kinds.map(String::as_str).collect();
+    if kinds.is_empty() {
+        return html! {};
+    }
+    html! {
+        <Alert inline=true r#type={AlertType::Warning} title={format!("Could not list {}", kinds.join(", "))} />
+    }
+}
+
+fn resources(resources: &[NamespaceResource]) -> Html {
+    if resources.is_empty() {
+        return html! { <p>{"The namespace is empty."}</p> };
+    }
+    html! {
+        <table class="pf-v5-c-table pf-m-compact">
+            <thead><tr><th>{"Kind"}</th><th>{"Name"}</th><th>{"Status"}</th></tr></thead>
+            <tbody>
+                { for resources.iter().map(|resource| html! {
+                    <tr>
+                        <td>{&resource.kind}</td>
+                        <td>{&resource.name}</td>
+                        <td>{resource.status.clone().unwrap_or_default()}</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
+
+fn events(events: &[NamespaceEvent]) -> Html {
+    if events.is_empty() {
+        return html! { <p>{"No recent events."}</p> };
+    }
+    html! {
+        <table class="pf-v5-c-table pf-m-compact">
+            <thead>
+                <tr><th>{"Last Seen"}</th><th>{"Type"}</th><th>{"Reason"}</th><th>{"Object"}</th><th>{"Message"}</th></tr>
+            </thead>
+            <tbody>
+                { for events.iter().map(|event| html! {
+                    <tr>
+                        <td>{event.last_seen.clone().unwrap_or_default()}</td>
+                        <td>{&event.event_type}</td>
+                        <td>{format!("{} (x{})", event.reason, event.count)}</td>
+                        <td>{&event.object}</td>
+                        <td>{&event.message}</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
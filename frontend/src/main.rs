@@ -1,60 +1,107 @@
-use std::{ rc::Rc, time::Duration};
+mod auth;
+mod bulk;
+mod dashboard;
+mod details;
+mod metadata;
+
+use std::{collections::BTreeMap, fmt, rc::Rc, time::Duration};
 use yew_hooks::prelude::*;
+use yew_router::prelude::*;
 
-use common::model::user::{User, VmsVersion};
+use common::api::client::{ApiClient, ClientError, UserPage};
+use common::model::query::{SortField, SortOrder, UserQuery};
+use common::model::user::{CreateUserRequest, User, VmsVersion};
 use patternfly_yew::prelude::*;
-use reqwasm::http::Request;
-use serde::Serialize;
 use yew::{html::ChildrenRenderer, prelude::*};
 
-async fn get_users() -> Vec<User> {
-    let url = "/api/user".to_string();
-    Request::get(&url)
-        .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+#[derive(Clone, Routable, PartialEq)]
+pub enum Route {
+    #[at("/")]
+    Home,
+    #[at("/users/:id")]
+    User { id: String },
+    #[at("/dashboard")]
+    Dashboard,
+    #[at("/login")]
+    Login,
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
+fn switch(route: Route) -> Html {
+    match route {
+        Route::Home => html! { <auth::RequireSession><AdminPanel /></auth::RequireSession> },
+        Route::User { id } => {
+            html! { <auth::RequireSession><details::UserDetailsPage {id} /></auth::RequireSession> }
+        }
+        Route::Dashboard => {
+            html! { <auth::RequireSession><dashboard::Dashboard /></auth::RequireSession> }
+        }
+        Route::Login => html! { <auth::LoginPage /> },
+        Route::NotFound => html! { <Redirect<Route> to={Route::Home} /> },
+    }
+}
+
+fn api_client() -> ApiClient {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    let client = ApiClient::new(origin);
+    match auth::stored_session() {
+        Some(session) => client.with_token(session.token),
+        None => client,
+    }
+}
+
+async fn get_users(query: &UserQuery) -> Result<UserPage, ClientError> {
+    auth::check_session(api_client().query_users(query).await)
 }
 
-#[derive(Serialize)]
-pub struct CreateUserRequest {
-    user_name: String,
-    vms_version: String,
+async fn create_user(new_user: CreateUserRequest) -> Result<User, ClientError> {
+    auth::check_session(api_client().create_user(&new_user).await)
+}
+async fn delete_user(user: &str) -> Result<User, ClientError> {
+    auth::check_session(api_client().delete_user(user).await)
 }
-async fn create_user(new_user: CreateUserRequest) -> Vec<User> {
-    let url = "/api/user".to_string();
-    let body = serde_json::to_string(&new_user).unwrap();
-    Request::post(&url)
-        .header("Content-Type", "application/json")
-        .body(body)
-        .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+async fn upgrade_user(user: &str, vms_version: VmsVersion) -> Result<User, ClientError> {
+    auth::check_session(api_client().upgrade_user(user, Some(vms_version)).await)
 }
-async fn delete_user(user: &str) -> User {
-    let url = format!("/api/user/{user}");
-    Request::delete(&url)
-        .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+
+/// Human readable reason for a failed call, preferring the backend's message.
+fn error_message(error: &ClientError) -> String {
+    match error {
+        ClientError::Status { status: 412, .. } => {
+            "The tenant was changed in the meantime, reload and try again".to_string()
+        }
+        ClientError::Status {
+            status, message, ..
+        } if message.is_empty() => {
+            format!("Request failed with status {status}")
+        }
+        ClientError::Status { message, .. } => message.clone(),
+        ClientError::Transport(_) => "The backend could not be reached".to_string(),
+    }
 }
-async fn upgrade_user(user: &str) -> User {
-    let url = format!("/api/user/{user}");
-    Request::patch(&url)
-        .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap()
+
+fn toast_result<T>(toaster: &Option<Toaster>, result: &Result<T, ClientError>, success: String) {
+    if let Some(toaster) = toaster {
+        let toast = match result {
+            Ok(_) => Toast {
+                title: success,
+                r#type: AlertType::Success,
+                timeout: Some(Duration::from_secs(5)),
+                ..Default::default()
+            },
+            Err(error) => Toast {
+                title: error_message(error),
+                r#type: AlertType::Danger,
+                timeout: Some(Duration::from_secs(10)),
+                ..Default::default()
+            },
+        };
+        toaster.toast(toast);
+    }
 }
 
 #[derive(Properties, PartialEq)]
@@ -64,17 +111,86 @@ pub struct UserProps {
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum ColumnsUserList {
+    Select,
     Name,
     Version,
+    Age,
     Link,
 }
 
+/// Compact age of a tenant, such as `3d` or `5h`, as `kubectl get` shows it.
+fn age(created_at: &Option<String>) -> String {
+    let Some(created) = created_at
+        .as_deref()
+        .map(js_sys::Date::parse)
+        .filter(|ms| !ms.is_nan())
+    else {
+        return "-".to_string();
+    };
+    let seconds = ((js_sys::Date::now() - created) / 1000.0).max(0.0) as u64;
+    match seconds {
+        seconds if seconds < 60 => format!("{seconds}s"),
+        seconds if seconds < 60 * 60 => format!("{}m", seconds / 60),
+        seconds if seconds < 24 * 60 * 60 => format!("{}h", seconds / (60 * 60)),
+        seconds => format!("{}d", seconds / (24 * 60 * 60)),
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum CreatedWithin {
+    Day,
+    Week,
+    Month,
+}
+
+impl CreatedWithin {
+    const ALL: [CreatedWithin; 3] = [
+        CreatedWithin::Day,
+        CreatedWithin::Week,
+        CreatedWithin::Month,
+    ];
+
+    /// The RFC 3339 timestamp this far back from now.
+    fn since(self) -> String {
+        let days = match self {
+            CreatedWithin::Day => 1.0,
+            CreatedWithin::Week => 7.0,
+            CreatedWithin::Month => 30.0,
+        };
+        let since = js_sys::Date::new_0();
+        since.set_time(js_sys::Date::now() - days * 24.0 * 60.0 * 60.0 * 1000.0);
+        since.to_iso_string().into()
+    }
+}
+
+impl fmt::Display for CreatedWithin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CreatedWithin::Day => "Last 24 hours",
+            CreatedWithin::Week => "Last 7 days",
+            CreatedWithin::Month => "Last 30 days",
+        })
+    }
+}
+
 impl TableEntryRenderer<ColumnsUserList> for User {
     fn render_cell(&self, ctx: CellContext<ColumnsUserList>) -> Cell {
         match ctx.column {
-            ColumnsUserList::Name => html!({ &self.user_name }),
+            ColumnsUserList::Select => html!(
+                <ContextProvider<User> context={self.clone()}>
+                    <bulk::SelectRow />
+                </ContextProvider<User>>
+            ),
+            ColumnsUserList::Name => html!(
+                <>
+                <Link<Route> to={Route::User { id: self.user_name.clone() }}>{ &self.user_name }</Link<Route>>
+                { for self.metadata.display_name.as_ref().map(|name| html!(
+                    <span class="pf-v5-u-ml-sm pf-v5-u-color-200">{ name }</span>
+                )) }
+                </>
+            ),
             ColumnsUserList::Version => {
-                if self.vms_version == VmsVersion::XProtect2024R1{
+                if self.vms_version == VmsVersion::latest(){
 
                     html!(<b>{ self.vms_version.to_string() }</b>)
                 }else{
@@ -82,15 +198,18 @@ impl TableEntryRenderer<ColumnsUserList> for User {
                     html!({ self.vms_version.to_string() })
                 }
             },
+            ColumnsUserList::Age => html!(
+                <span title={self.created_at.clone().unwrap_or_default()}>{ age(&self.created_at) }</span>
+            ),
             ColumnsUserList::Link => {
                 let mut upgrade = html!();
 
-                if self.vms_version != VmsVersion::XProtect2024R1{
-                    upgrade = html!(<ContextProvider<String> context={self.user_name.to_string().clone()}>
+                if !self.vms_version.newer_versions().is_empty(){
+                    upgrade = html!(<ContextProvider<User> context={self.clone()}>
                                         <Upgrade />
-                                    </ContextProvider<String>>);
+                                    </ContextProvider<User>>);
                 }
-                
+
                 html!(
                 <>
                 <ContextProvider<String> context={self.user_name.to_string().clone()}>
@@ -108,115 +227,497 @@ impl TableEntryRenderer<ColumnsUserList> for User {
 #[function_component(Trash)]
 fn trash_button() -> Html {
     let user_name = use_context::<String>().expect("no ctx found");
+    let toaster = use_toaster();
+
+    let busy = use_state_eq(|| false);
 
-    let events: yew::UseStateHandle<Option<User>> = use_state_eq(|| None);
-    
-    let onclick = {
+    let backdropper = use_backdrop();
+
+    let onconfirm = {
         let user_name = user_name.clone();
-        let events = events.clone();
+        let busy = busy.clone();
         Callback::from(move |_| {
-            let events = events.clone();
+            let busy = busy.clone();
             let user_name = user_name.clone();
+            let toaster = toaster.clone();
+            busy.set(true);
             wasm_bindgen_futures::spawn_local(async move {
-                    let deleted_user = delete_user(&user_name)
-                    .await;
-                events.set(Some(deleted_user));
-            });})
+                let result = delete_user(&user_name).await;
+                toast_result(
+                    &toaster,
+                    &result,
+                    format!("Deleted - Username: {user_name}"),
+                );
+                // The row disappears with the next reload; only re-enable on failure.
+                if result.is_err() {
+                    busy.set(false);
+                }
+            });
+        })
     };
 
-    if let Some(_) = &*events {
+    let onclick = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper.open(
+                html!(<DeleteDialog user_name={user_name.clone()} onconfirm={onconfirm.clone()} />),
+            );
+        }
+    });
+
+    if *busy {
         html!(<Button disabled=true variant={ButtonVariant::Plain}> <Spinner size={SpinnerSize::Md} /></Button>)
+    } else {
+        html!(<Button onclick={onclick} variant={ButtonVariant::Plain} icon={Icon::Trash} />)
     }
-    else {        
-            html!(<Button onclick={onclick} variant={ButtonVariant::Plain} icon={Icon::Trash} />)
-        }
 }
 
 #[function_component(Upgrade)]
 fn upgrade_button() -> Html {
-    let user_name = use_context::<String>().expect("no ctx found");
+    let user = use_context::<User>().expect("no ctx found");
+    let toaster = use_toaster();
 
-    let events: yew::UseStateHandle<Option<User>> = use_state_eq(|| None);
-    
-    let onclick = {
-        let user_name = user_name.clone();
-        let events = events.clone();
-        Callback::from(move |_| {
-            let events = events.clone();
+    let busy = use_state_eq(|| false);
+    let backdropper = use_backdrop();
+
+    let onconfirm = {
+        let user_name = user.user_name.clone();
+        let busy = busy.clone();
+        Callback::from(move |vms_version: VmsVersion| {
+            let busy = busy.clone();
             let user_name = user_name.clone();
+            let toaster = toaster.clone();
+            busy.set(true);
             wasm_bindgen_futures::spawn_local(async move {
-                let upgraded_user = upgrade_user(&user_name)
-                    .await;
-                events.set(Some(upgraded_user));
-            });})
+                let result = upgrade_user(&user_name, vms_version).await;
+                toast_result(
+                    &toaster,
+                    &result,
+                    format!("Upgraded - Username: {user_name}, XProtect Version: {vms_version}"),
+                );
+                if result.is_err() {
+                    busy.set(false);
+                }
+            });
+        })
     };
 
-    if let Some(events) = &*events {
+    let onclick = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper
+                .open(html!(<UpgradeDialog user={user.clone()} onconfirm={onconfirm.clone()} />));
+        }
+    });
+
+    if *busy {
         html!(<Button disabled=true variant={ButtonVariant::Plain}> <Spinner size={SpinnerSize::Md} /></Button>)
+    } else {
+        html!(<Button onclick={onclick} variant={ButtonVariant::Plain} icon={Icon::ArrowCircleUp} />)
     }
-    else {        
-            html!(<Button onclick={onclick} variant={ButtonVariant::Plain} icon={Icon::ArrowCircleUp} />)
+}
+
+fn upgrade_warnings(current: VmsVersion, target: VmsVersion) -> Vec<String> {
+    let mut warnings = vec!["The upgrade cannot be rolled back.".to_string()];
+    let skipped: Vec<String> = current
+        .newer_versions()
+        .into_iter()
+        .filter(|version| *version < target)
+        .map(|version| version.to_string())
+        .collect();
+    if !skipped.is_empty() {
+        warnings.push(format!(
+            "Skips the intermediate releases {}.",
+            skipped.join(", ")
+        ));
+    }
+    warnings
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DeleteDialogProps {
+    pub user_name: String,
+    pub onconfirm: Callback<()>,
+}
+
+/// Asks for the tenant name to be typed before deleting it.
+#[function_component(DeleteDialog)]
+fn delete_dialog(props: &DeleteDialogProps) -> Html {
+    let backdropper = use_backdrop();
+    let typed = use_state_eq(String::new);
+
+    let onchange = {
+        let typed = typed.clone();
+        Callback::from(move |value| typed.set(value))
+    };
+    let onclose = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper.close();
+        }
+    });
+    let ondelete = {
+        let onconfirm = props.onconfirm.clone();
+        let onclose = onclose.clone();
+        Callback::from(move |_| {
+            onclose.emit(());
+            onconfirm.emit(());
+        })
+    };
+
+    let footer = html! {<>
+        <Button variant={ButtonVariant::Danger} disabled={*typed != props.user_name} onclick={ondelete}>{"Delete"}</Button>
+        <Button variant={ButtonVariant::Link} onclick={onclose.reform(|_| ())}>{"Cancel"}</Button>
+    </>};
+
+    html! {
+        <Modal
+            title={format!("Delete {}?", props.user_name)}
+            variant={ModalVariant::Small}
+            onclose={onclose}
+            footer={footer}
+        >
+            <p>{"This removes the tenant namespace and everything running in it."}</p>
+            <Form>
+                <FormGroup label={format!("Type {} to confirm", props.user_name)}>
+                    <TextInput {onchange} value={(*typed).clone()} />
+                </FormGroup>
+            </Form>
+        </Modal>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct UpgradeDialogProps {
+    pub user: User,
+    pub onconfirm: Callback<VmsVersion>,
+}
+
+/// Offers the releases newer than the tenant's, the latest preselected.
+#[function_component(UpgradeDialog)]
+fn upgrade_dialog(props: &UpgradeDialogProps) -> Html {
+    let backdropper = use_backdrop();
+    let target = use_state_eq(VmsVersion::latest);
+
+    let onselect = {
+        let target = target.clone();
+        Callback::from(move |version: VmsVersion| target.set(version))
+    };
+
+    let onclose = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper.close();
         }
+    });
+    let onupgrade = {
+        let onconfirm = props.onconfirm.clone();
+        let onclose = onclose.clone();
+        let target = *target;
+        Callback::from(move |_| {
+            onclose.emit(());
+            onconfirm.emit(target);
+        })
+    };
+
+    let footer = html! {<>
+        <Button variant={ButtonVariant::Primary} onclick={onupgrade}>{"Upgrade"}</Button>
+        <Button variant={ButtonVariant::Link} onclick={onclose.reform(|_| ())}>{"Cancel"}</Button>
+    </>};
+
+    html! {
+        <Modal
+            title={format!("Upgrade {}?", props.user.user_name)}
+            variant={ModalVariant::Small}
+            onclose={onclose}
+            footer={footer}
+        >
+            <p>
+                <b>{props.user.vms_version.to_string()}</b>
+                {" \u{2192} "}
+                <SimpleSelect<VmsVersion>
+                    selected={Some(*target)}
+                    entries={props.user.vms_version.newer_versions()}
+                    {onselect}
+                />
+            </p>
+            { for upgrade_warnings(props.user.vms_version, *target).into_iter().map(|warning| html! {
+                <Alert inline=true r#type={AlertType::Warning} title={warning} />
+            }) }
+        </Modal>
+    }
 }
 
+const PAGE_SIZES: [usize; 3] = [10, 25, 50];
+
 #[function_component(UsersView)]
 fn users_view(props: &UserProps) -> Html {
     let reload = props.reload;
     let users = use_state(|| None);
-    let users_old = use_state(|| None);
+    let users_old = use_state(|| None::<UserPage>);
+    let error = use_state_eq(|| None::<String>);
+
+    let search = use_state_eq(String::new);
+    let version = use_state_eq(|| None::<VmsVersion>);
+    // The cut-off is fixed when picked so the query does not change on every render.
+    let created = use_state_eq(|| None::<(CreatedWithin, String)>);
+    let sortby = use_state_eq(|| None::<TableHeaderSortBy<ColumnsUserList>>);
+    let offset = use_state_eq(|| 0);
+    let limit = use_state_eq(|| PAGE_SIZES[0]);
+    let selected = use_state_eq(|| Rc::new(BTreeMap::<String, User>::new()));
+    // Bumped after bulk operations to reload independently of the parent.
+    let refresh = use_state_eq(|| 0);
+
+    // Filtering, sorting and paging happen on the server so large tenant
+    // lists are never transferred in full.
+    let query = UserQuery {
+        search: Some((*search).clone()).filter(|search| !search.is_empty()),
+        vms_version: *version,
+        created_after: (*created).as_ref().map(|(_, since)| since.clone()),
+        created_before: None,
+        sort: (*sortby).as_ref().map(|sortby| match sortby.index {
+            ColumnsUserList::Version => SortField::Version,
+            ColumnsUserList::Age => SortField::Created,
+            _ => SortField::Name,
+        }),
+        // Ascending age means the newest tenants first, i.e. creation time descending.
+        order: (*sortby).as_ref().map(|sortby| {
+            if sortby.asc != (sortby.index == ColumnsUserList::Age) {
+                SortOrder::Asc
+            } else {
+                SortOrder::Desc
+            }
+        }),
+        page: Some(*offset / *limit + 1),
+        per_page: Some(*limit),
+    };
     {
         let users = users.clone();
         let users_old = users_old.clone();
-        use_effect_with(reload, move |_| {
+        let error = error.clone();
+        use_effect_with((reload, *refresh, query), move |(_, _, query)| {
             let users = users.clone();
             let users_old = users_old.clone();
+            let query = query.clone();
             wasm_bindgen_futures::spawn_local(async move {
                 users.set(None);
-                let fetched_user = get_users().await;
-                users.set(Some(fetched_user.clone()));
-                users_old.set(Some(fetched_user));
+                // On failure keep showing the last known list; the periodic
+                // reload retries.
+                match get_users(&query).await {
+                    Ok(fetched_user) => {
+                        error.set(None);
+                        users.set(Some(fetched_user.clone()));
+                        users_old.set(Some(fetched_user));
+                    }
+                    Err(e) => error.set(Some(error_message(&e))),
+                }
             });
             || ()
         });
     }
 
-    if let Some(users) = &*users {
-        if let Some(users_old) = &*users_old {
-            html! {
-                <ContextProvider<Vec<User>> context={users_old.clone()}>
-                    <UserGrid />
-                </ContextProvider<Vec<User>>>
-            }
-        } else {
-            html! {
-                <ContextProvider<Vec<User>> context={users.clone()}>
-                    <UserGrid />
-                </ContextProvider<Vec<User>>>
-            }
-        }
-    } else if let Some(users_old) = &*users_old {
-        html! {<>
-            <ContextProvider<Vec<User>> context={users_old.clone()}>
-                <UserGrid />
-            </ContextProvider<Vec<User>>>
-        </>
+    let onsearch = {
+        let search = search.clone();
+        let offset = offset.clone();
+        Callback::from(move |value: String| {
+            search.set(value);
+            offset.set(0);
+        })
+    };
+    let onversion = {
+        let version = version.clone();
+        let offset = offset.clone();
+        Callback::from(move |value: VmsVersion| {
+            version.set(Some(value));
+            offset.set(0);
+        })
+    };
+    let onclearversion = {
+        let version = version.clone();
+        Callback::from(move |_| version.set(None))
+    };
+    let oncreated = {
+        let created = created.clone();
+        let offset = offset.clone();
+        Callback::from(move |value: CreatedWithin| {
+            created.set(Some((value, value.since())));
+            offset.set(0);
+        })
+    };
+    let onclearcreated = {
+        let created = created.clone();
+        Callback::from(move |_| created.set(None))
+    };
+    let onsort = {
+        let sortby = sortby.clone();
+        Callback::from(move |value: TableHeaderSortBy<ColumnsUserList>| sortby.set(Some(value)))
+    };
+
+    let selection = {
+        let selected = selected.clone();
+        bulk::Selection {
+            selected: (*selected).clone(),
+            ontoggle: Callback::from(move |(user, checked): (User, bool)| {
+                let mut next = (**selected).clone();
+                if checked {
+                    next.insert(user.user_name.clone(), user);
+                } else {
+                    next.remove(&user.user_name);
+                }
+                selected.set(Rc::new(next));
+            }),
         }
-    } else {
-        html! { <div>{"Loading..."}</div> }
+    };
+    let onselectpage = {
+        let selected = selected.clone();
+        let page = (*users_old).clone();
+        Callback::from(move |_| {
+            let mut next = (**selected).clone();
+            for user in page.iter().flat_map(|page| page.users.iter()) {
+                next.insert(user.user_name.clone(), user.clone());
+            }
+            selected.set(Rc::new(next));
+        })
+    };
+    let onclearselection = {
+        let selected = selected.clone();
+        Callback::from(move |_| selected.set(Rc::new(BTreeMap::new())))
+    };
+    let onbulkdone = {
+        let selected = selected.clone();
+        let refresh = refresh.clone();
+        Callback::from(move |_| {
+            selected.set(Rc::new(BTreeMap::new()));
+            refresh.set(*refresh + 1);
+        })
+    };
+
+    let total = (*users_old)
+        .as_ref()
+        .map(|page| page.total)
+        .unwrap_or_default();
+    let onnavigation = {
+        let offset = offset.clone();
+        let limit = *limit;
+        Callback::from(move |navigation| {
+            let last = total.saturating_sub(1) / limit * limit;
+            offset.set(match navigation {
+                Navigation::First => 0,
+                Navigation::Previous => offset.saturating_sub(limit),
+                Navigation::Next => (*offset + limit).min(last),
+                Navigation::Last => last,
+                Navigation::Page(page) => (page * limit).min(last),
+            });
+        })
+    };
+    let onlimit = {
+        let limit = limit.clone();
+        let offset = offset.clone();
+        Callback::from(move |value| {
+            limit.set(value);
+            offset.set(0);
+        })
+    };
+
+    let alert = match &*error {
+        Some(message) => html! {
+            <Alert inline=true r#type={AlertType::Danger} title={format!("Could not load users: {message}")} />
+        },
+        None => html!(),
+    };
+    let version_chip = match *version {
+        Some(version) => html! {
+            <ChipGroup label="Version">
+                <Chip text={version.to_string()} onclose={onclearversion} />
+            </ChipGroup>
+        },
+        None => html!(),
+    };
+    let created_chip = match &*created {
+        Some((within, _)) => html! {
+            <ChipGroup label="Created">
+                <Chip text={within.to_string()} onclose={onclearcreated} />
+            </ChipGroup>
+        },
+        None => html!(),
+    };
+
+    html! {<>
+        {alert}
+        <Toolbar>
+            <ToolbarContent>
+                <ToolbarItem>
+                    <TextInput placeholder="Search by name" onchange={onsearch} value={(*search).clone()} />
+                </ToolbarItem>
+                <ToolbarItem>
+                    <SimpleSelect<VmsVersion>
+                        placeholder="Filter by version"
+                        selected={*version}
+                        entries={VmsVersion::all()}
+                        onselect={onversion}
+                    />
+                </ToolbarItem>
+                <ToolbarItem>{version_chip}</ToolbarItem>
+                <ToolbarItem>
+                    <SimpleSelect<CreatedWithin>
+                        placeholder="Filter by creation"
+                        selected={(*created).as_ref().map(|(within, _)| *within)}
+                        entries={CreatedWithin::ALL.to_vec()}
+                        onselect={oncreated}
+                    />
+                </ToolbarItem>
+                <ToolbarItem>{created_chip}</ToolbarItem>
+                <ToolbarItem>
+                    <Button variant={ButtonVariant::Link} onclick={onselectpage}>{"Select page"}</Button>
+                </ToolbarItem>
+            </ToolbarContent>
+        </Toolbar>
+        <bulk::BulkToolbar selected={(*selected).clone()} onclear={onclearselection} ondone={onbulkdone} />
+        <ContextProvider<bulk::Selection> context={selection}>
+            {users_table(&users, &users_old, error.is_some(), (*sortby).clone(), onsort)}
+        </ContextProvider<bulk::Selection>>
+        <Pagination
+            total_entries={Some(total)}
+            offset={*offset}
+            entries_per_page_choices={PAGE_SIZES.to_vec()}
+            selected_choice={*limit}
+            {onnavigation}
+            {onlimit}
+        />
+    </>}
+}
+
+fn users_table(
+    users: &Option<UserPage>,
+    users_old: &Option<UserPage>,
+    failed: bool,
+    sortby: Option<TableHeaderSortBy<ColumnsUserList>>,
+    onsort: Callback<TableHeaderSortBy<ColumnsUserList>>,
+) -> Html {
+    // Keep the previous page on screen while the next one loads.
+    match users.as_ref().or(users_old.as_ref()) {
+        Some(page) => html! {
+            <UserGrid users={page.users.clone()} {sortby} {onsort} />
+        },
+        None if failed => html!(),
+        None => html! { <div>{"Loading..."}</div> },
     }
 }
 
-#[function_component(UserGrid)]
-pub fn user_grid() -> Html {
-    let entities = use_context::<Vec<User>>().expect("no ctx found");
+#[derive(Properties, PartialEq)]
+pub struct UserGridProps {
+    pub users: Vec<User>,
+    pub sortby: Option<TableHeaderSortBy<ColumnsUserList>>,
+    pub onsort: Callback<TableHeaderSortBy<ColumnsUserList>>,
+}
 
-    let entries: Rc<Vec<User>> = use_memo((), |()| entities);
+#[function_component(UserGrid)]
+pub fn user_grid(props: &UserGridProps) -> Html {
+    let entries: Rc<Vec<User>> = use_memo(props.users.clone(), |users| users.clone());
     let (entries, _) = use_table_data(MemoizedTableModel::new(entries));
 
     let header = html_nested! {
         <TableHeader<ColumnsUserList>>
-            <TableColumn<ColumnsUserList> label="User Name" index={ColumnsUserList::Name}/>
-            <TableColumn<ColumnsUserList> label="VMS Version" index={ColumnsUserList::Version} />
+            <TableColumn<ColumnsUserList> label=" " index={ColumnsUserList::Select}/>
+            <TableColumn<ColumnsUserList> label="User Name" index={ColumnsUserList::Name} sortby={props.sortby.clone()} onsort={props.onsort.clone()} />
+            <TableColumn<ColumnsUserList> label="VMS Version" index={ColumnsUserList::Version} sortby={props.sortby.clone()} onsort={props.onsort.clone()} />
+            <TableColumn<ColumnsUserList> label="Age" index={ColumnsUserList::Age} sortby={props.sortby.clone()} onsort={props.onsort.clone()} />
             <TableColumn<ColumnsUserList> label=" " index={ColumnsUserList::Link}/>
         </TableHeader<ColumnsUserList>>
     };
@@ -240,7 +741,9 @@ pub fn app() -> Html {
     html! {
         <BackdropViewer>
             <ToastViewer>
-                <AdminPanel />
+                <BrowserRouter>
+                    <Switch<Route> render={switch} />
+                </BrowserRouter>
             </ToastViewer>
         </BackdropViewer>
     }
@@ -285,47 +788,57 @@ pub fn login_page_example() -> Html {
         let vms_version = vms_version.clone();
         let selected = selected.clone();
         Callback::from(move |_| {
-            let reload = reload.clone();
-            {
-                let username = username.clone();
-                let vms_version = vms_version.clone();
-                wasm_bindgen_futures::spawn_local(async move {
-                    let fetched_user = create_user(CreateUserRequest {
-                        user_name: username.to_string(),
-                        vms_version: vms_version.to_string(),
-                    })
-                    .await;
-                });
+            let Some(version) = *selected else {
+                return;
+            };
+            if (*username).is_empty() {
+                return;
             }
-            if let Some(toaster) = &toaster {
-                if !(*username).is_empty() && !(*vms_version).is_empty(){
-                    toaster.toast(Toast {
-                        title: format!(
-                            "Created - Username: {}, XProtect Version: {}",
-                            &*username, &*vms_version
-                        ),
-                        timeout: Some(Duration::from_secs(5)),
-                        ..Default::default()
-                    });
+            let toaster = toaster.clone();
+            let reload = reload.clone();
+            let username = username.clone();
+            let vms_version = vms_version.clone();
+            let selected = selected.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = create_user(CreateUserRequest {
+                    user_name: username.to_string(),
+                    vms_version: version,
+                })
+                .await;
+                toast_result(
+                    &toaster,
+                    &result,
+                    format!(
+                        "Created - Username: {}, XProtect Version: {}",
+                        &*username, version
+                    ),
+                );
+                // Keep the form filled in on failure so it can be corrected.
+                if result.is_ok() {
+                    username.set("".to_string());
+                    vms_version.set("".to_string());
+                    selected.set(None);
+                    reload.set(*reload + 1);
                 }
-            }
-            username.set("".to_string());
-            vms_version.set("".to_string());
-            selected.set(None);
-            reload.set(*reload + 1);
+            });
         })
     };
 
     let band = ChildrenRenderer::new(vec![
+        html! {<Link<Route> to={Route::Dashboard}>{"Fleet overview"}</Link<Route>>},
+        html! {<auth::LogoutButton />},
         html! {<UsersView reload={*reload}/>},
         //html! {<a href="#">{"Some link"}</a>},
         //html! {<>{"Some other"}<a href="#">{" link"}</a></>},
     ]);
-        {
+    {
         let reload = reload.clone();
-        use_interval(move || {
-            reload.set(*reload + 1);
-        }, 15000);
+        use_interval(
+            move || {
+                reload.set(*reload + 1);
+            },
+            15000,
+        );
     }
     html! {
         <>
@@ -334,7 +847,7 @@ pub fn login_page_example() -> Html {
                    // {header}
                   //  {footer}
                 >
-                            <img alt="Kitodar and friends" src="img/kitodar-and-friends.png" style="margin-left: auto; margin-right: auto; " title="Kitodar and friends"/>
+                            <img alt="Kitodar and friends" src="/img/kitodar-and-friends.png" style="margin-left: auto; margin-right: auto; " title="Kitodar and friends"/>
                             <div style="color: white">
                                 {"Authors: "}
                                 <List>
@@ -356,7 +869,7 @@ pub fn login_page_example() -> Html {
                                 <SimpleSelect<VmsVersion>
                                     placeholder="Pick a VMS Version"
                                     selected={*selected}
-                                    entries={vec![VmsVersion::XProtect2023R1, VmsVersion::XProtect2023R2, VmsVersion::XProtect2023R3, VmsVersion::XProtect2024R1]}
+                                    entries={VmsVersion::all()}
                                     {onselect}
                                 />
                                 </FormGroup>
@@ -0,0 +1,109 @@
+use common::model::user::UserMetadata;
+use patternfly_yew::prelude::*;
+use yew::prelude::*;
+
+use crate::{api_client, auth::check_session, toast_result};
+
+#[derive(Properties, PartialEq)]
+pub struct MetadataDialogProps {
+    pub user_name: String,
+    pub metadata: UserMetadata,
+    /// ETag of the tenant the metadata was read from, so that concurrent
+    /// edits are rejected rather than overwritten.
+    pub etag: Option<String>,
+    pub onsaved: Callback<()>,
+}
+
+/// Edits the descriptive fields of a tenant. Cleared fields are removed.
+#[function_component(MetadataDialog)]
+pub fn metadata_dialog(props: &MetadataDialogProps) -> Html {
+    let backdropper = use_backdrop();
+    let toaster = use_toaster();
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let display_name = use_state_eq(|| text(&props.metadata.display_name));
+    let owner_email = use_state_eq(|| text(&props.metadata.owner_email));
+    let cost_center = use_state_eq(|| text(&props.metadata.cost_center));
+    let notes = use_state_eq(|| text(&props.metadata.notes));
+    let busy = use_state_eq(|| false);
+
+    let setter = |state: &UseStateHandle<String>| {
+        let state = state.clone();
+        Callback::from(move |value: String| state.set(value))
+    };
+
+    let onclose = Callback::from(move |_| {
+        if let Some(backdropper) = &backdropper {
+            backdropper.close();
+        }
+    });
+    let onsave = {
+        let user_name = props.user_name.clone();
+        let etag = props.etag.clone();
+        let onsaved = props.onsaved.clone();
+        let onclose = onclose.clone();
+        let busy = busy.clone();
+        let metadata = UserMetadata {
+            display_name: Some(display_name.trim().to_string()),
+            owner_email: Some(owner_email.trim().to_string()),
+            cost_center: Some(cost_center.trim().to_string()),
+            notes: Some(notes.trim().to_string()),
+        };
+        Callback::from(move |_| {
+            let user_name = user_name.clone();
+            let etag = etag.clone();
+            let metadata = metadata.clone();
+            let onsaved = onsaved.clone();
+            let onclose = onclose.clone();
+            let toaster = toaster.clone();
+            let busy = busy.clone();
+            busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = check_session(
+                    api_client()
+                        .update_user_metadata(&user_name, &metadata, etag.as_deref())
+                        .await,
+                );
+                toast_result(
+                    &toaster,
+                    &result,
+                    format!("Updated - Username: {user_name}"),
+                );
+                busy.set(false);
+                // Keep the dialog open on failure so the input can be corrected.
+                if result.is_ok() {
+                    onclose.emit(());
+                    onsaved.emit(());
+                }
+            });
+        })
+    };
+
+    let footer = html! {<>
+        <Button variant={ButtonVariant::Primary} disabled={*busy} onclick={onsave}>{"Save"}</Button>
+        <Button variant={ButtonVariant::Link} onclick={onclose.reform(|_| ())}>{"Cancel"}</Button>
+    </>};
+
+    html! {
+        <Modal
+            title={format!("Edit {}", props.user_name)}
+            variant={ModalVariant::Medium}
+            onclose={onclose}
+            footer={footer}
+        >
+            <Form>
+                <FormGroup label="Display Name">
+                    <TextInput onchange={setter(&display_name)} value={(*display_name).clone()} />
+                </FormGroup>
+                <FormGroup label="Owner Email">
+                    <TextInput r#type={TextInputType::Email} onchange={setter(&owner_email)} value={(*owner_email).clone()} />
+                </FormGroup>
+                <FormGroup label="Cost Center">
+                    <TextInput onchange={setter(&cost_center)} value={(*cost_center).clone()} />
+                </FormGroup>
+                <FormGroup label="Notes">
+                    <TextArea onchange={setter(&notes)} value={(*notes).clone()} />
+                </FormGroup>
+            </Form>
+        </Modal>
+    }
+}
@@ -0,0 +1,270 @@
+mod output;
+
+use clap::{Parser, Subcommand};
+use common::api::client::{ApiClient, ClientError};
+use common::model::user::{CreateUserRequest, User, VmsVersion};
+use output::{print_user, print_users, OutputFormat};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+/// Command-line client for the kitodar tenant API.
+#[derive(Parser)]
+#[command(name = "kitodarctl", version)]
+struct Cli {
+    /// Base URL of the kitodar backend.
+    #[arg(
+        long,
+        env = "KITODAR_SERVER",
+        default_value = "http://localhost:80",
+        global = true
+    )]
+    server: String,
+
+    /// Bearer token sent with every request.
+    #[arg(long, env = "KITODAR_TOKEN", hide_env_values = true, global = true)]
+    token: Option<String>,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List tenants.
+    List {
+        /// Filter by field, e.g. `vms_version=XProtect2023R1,server_type!=vms`.
+        #[arg(short = 'l', long, value_parser = Selectors::parse)]
+        selector: Option<Selectors>,
+    },
+    /// Show a single tenant.
+    Get { user_name: String },
+    /// Create a tenant.
+    Create {
+        user_name: String,
+        #[arg(long, value_parser = parse_vms_version)]
+        vms_version: VmsVersion,
+    },
+    /// Upgrade a tenant, by default to the latest VMS version.
+    Upgrade {
+        user_name: String,
+        #[arg(long, value_parser = parse_vms_version)]
+        vms_version: Option<VmsVersion>,
+    },
+    /// Delete a tenant.
+    Delete { user_name: String },
+}
+
+/// Process exit codes, so scripts can branch on the kind of failure. Usage
+/// errors exit with 2, as reported by clap.
+#[derive(Debug, PartialEq)]
+enum Exit {
+    Success = 0,
+    Failure = 1,
+    NotFound = 3,
+    Conflict = 4,
+    Unreachable = 5,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+impl From<&ClientError> for Exit {
+    fn from(error: &ClientError) -> Self {
+        match error {
+            ClientError::Status { status: 404, .. } => Exit::NotFound,
+            ClientError::Status {
+                status: 409 | 412, ..
+            } => Exit::Conflict,
+            ClientError::Status { .. } => Exit::Failure,
+            ClientError::Transport(_) => Exit::Unreachable,
+        }
+    }
+}
+
+fn parse_vms_version(value: &str) -> Result<VmsVersion, String> {
+    VmsVersion::from_str(value).map_err(|_| format!("unknown VMS version `{value}`"))
+}
+
+#[derive(Clone)]
+struct Selector {
+    field: String,
+    value: String,
+    negated: bool,
+}
+
+impl Selector {
+    fn matches(&self, user: &User) -> bool {
+        let actual = match self.field.as_str() {
+            "user_name" => user.user_name.clone(),
+            "vms_version" => user.vms_version.to_string(),
+            _ => user.server_type.clone(),
+        };
+        (actual == self.value) != self.negated
+    }
+}
+
+#[derive(Clone)]
+struct Selectors(Vec<Selector>);
+
+impl Selectors {
+    fn parse(selectors: &str) -> Result<Selectors, String> {
+        selectors
+            .split(',')
+            .map(|selector| {
+                let (field, value, negated) = match selector.split_once("!=") {
+                    Some((field, value)) => (field, value, true),
+                    None => match selector.split_once('=') {
+                        Some((field, value)) => (field, value, false),
+                        None => return Err(format!("invalid selector `{selector}`")),
+                    },
+                };
+                match field.trim() {
+                    "user_name" | "vms_version" | "server_type" => Ok(Selector {
+                        field: field.trim().to_owned(),
+                        value: value.trim().to_owned(),
+                        negated,
+                    }),
+                    field => Err(format!("unknown selector field `{field}`")),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Selectors)
+    }
+
+    fn matches(&self, user: &User) -> bool {
+        self.0.iter().all(|selector| selector.matches(user))
+    }
+}
+
+async fn run(cli: Cli, client: ApiClient) -> Result<(), ClientError> {
+    match cli.command {
+        Command::List { selector } => {
+            let users: Vec<User> = client
+                .get_users()
+                .await?
+                .into_iter()
+                .filter(|user| match &selector {
+                    Some(selector) => selector.matches(user),
+                    None => true,
+                })
+                .collect();
+            print_users(&users, cli.output)
+        }
+        Command::Get { user_name } => print_user(&client.get_user(&user_name).await?, cli.output),
+        Command::Create {
+            user_name,
+            vms_version,
+        } => {
+            let request = CreateUserRequest {
+                user_name,
+                vms_version,
+            };
+            print_user(&client.create_user(&request).await?, cli.output)
+        }
+        Command::Upgrade {
+            user_name,
+            vms_version,
+        } => print_user(
+            &client.upgrade_user(&user_name, vms_version).await?,
+            cli.output,
+        ),
+        Command::Delete { user_name } => {
+            print_user(&client.delete_user(&user_name).await?, cli.output)
+        }
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut client = ApiClient::new(cli.server.clone());
+    if let Some(token) = &cli.token {
+        client = client.with_token(token);
+    }
+
+    let result = run(cli, client).await;
+
+    match result {
+        Ok(()) => Exit::Success.into(),
+        Err(e) => {
+            eprintln!("error: {e}");
+            Exit::from(&e).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command, Exit, Selectors};
+    use clap::Parser;
+    use common::api::client::{ApiClient, ClientError};
+    use common::model::user::{User, VmsVersion};
+
+    #[test]
+    fn selectors_match_on_equality_and_inequality() {
+        let selectors = Selectors::parse("vms_version=XProtect2023R1, server_type!=vms").unwrap();
+        let mut user = User::new("alice".to_owned(), VmsVersion::XProtect2023R1);
+        user.server_type = "standard".to_owned();
+        assert!(selectors.matches(&user));
+        user.server_type = "vms".to_owned();
+        assert!(!selectors.matches(&user));
+    }
+
+    #[test]
+    fn selectors_on_unknown_fields_are_rejected() {
+        assert!(Selectors::parse("foo=bar").is_err());
+        assert!(Selectors::parse("vms_version").is_err());
+    }
+
+    #[test]
+    fn connection_options_are_accepted_after_the_subcommand() {
+        let cli = Cli::try_parse_from([
+            "kitodarctl",
+            "get",
+            "alice",
+            "--server",
+            "http://kitodar",
+            "--token",
+            "secret",
+        ])
+        .unwrap();
+        assert_eq!(cli.server, "http://kitodar");
+        assert_eq!(cli.token.as_deref(), Some("secret"));
+        assert!(matches!(cli.command, Command::Get { .. }));
+    }
+
+    #[test]
+    fn status_codes_map_to_exit_codes() {
+        let status = |status| ClientError::Status {
+            status,
+            message: String::new(),
+            retry_after: None,
+        };
+        assert_eq!(Exit::from(&status(404)), Exit::NotFound);
+        assert_eq!(Exit::from(&status(409)), Exit::Conflict);
+        assert_eq!(Exit::from(&status(412)), Exit::Conflict);
+        assert_eq!(Exit::from(&status(500)), Exit::Failure);
+    }
+
+    #[tokio::test]
+    async fn refused_connections_exit_as_unreachable() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let error = ApiClient::new(format!("http://127.0.0.1:{port}"))
+            .get_users()
+            .await
+            .unwrap_err();
+        assert_eq!(Exit::from(&error), Exit::Unreachable);
+    }
+}
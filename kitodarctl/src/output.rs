@@ -0,0 +1,100 @@
+use clap::ValueEnum;
+use common::model::user::User;
+use serde::Serialize;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Yaml,
+}
+
+fn serialized<T: Serialize + ?Sized>(value: &T, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Yaml => serde_yaml::to_string(value).unwrap_or_default(),
+        _ => serde_json::to_string_pretty(value).unwrap_or_default() + "\n",
+    }
+}
+
+fn formatted(users: &[User], format: OutputFormat) -> String {
+    if format != OutputFormat::Table {
+        return serialized(users, format);
+    }
+
+    let rows: Vec<[String; 3]> = users
+        .iter()
+        .map(|user| {
+            [
+                user.user_name.clone(),
+                user.vms_version.to_string(),
+                user.server_type.clone(),
+            ]
+        })
+        .collect();
+    let header = ["NAME", "VERSION", "SERVER TYPE"].map(String::from);
+    let widths: Vec<usize> = (0..3)
+        .map(|i| {
+            rows.iter()
+                .chain(std::iter::once(&header))
+                .map(|row| row[i].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            format!(
+                "{:<w0$}   {:<w1$}   {}\n",
+                row[0],
+                row[1],
+                row[2],
+                w0 = widths[0],
+                w1 = widths[1]
+            )
+        })
+        .collect()
+}
+
+pub fn print_users(users: &[User], format: OutputFormat) {
+    print!("{}", formatted(users, format));
+}
+
+pub fn print_user(user: &User, format: OutputFormat) {
+    match format {
+        OutputFormat::Table => print_users(std::slice::from_ref(user), format),
+        _ => print!("{}", serialized(user, format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{formatted, OutputFormat};
+    use common::model::user::{User, VmsVersion};
+
+    fn users() -> Vec<User> {
+        vec![
+            User::new("alice".to_owned(), VmsVersion::XProtect2024R1),
+            User::new("bob".to_owned(), VmsVersion::XProtect2023R1),
+        ]
+    }
+
+    #[test]
+    fn tables_align_columns_to_the_widest_cell() {
+        let table = formatted(&users(), OutputFormat::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("NAME    VERSION  "));
+        assert!(lines[1].starts_with("alice   XProtect2024R1   "));
+        assert!(lines[2].starts_with("bob     XProtect2023R1   "));
+    }
+
+    #[test]
+    fn json_and_yaml_round_trip() {
+        let json = formatted(&users(), OutputFormat::Json);
+        assert_eq!(serde_json::from_str::<Vec<User>>(&json).unwrap(), users());
+        let yaml = formatted(&users(), OutputFormat::Yaml);
+        assert_eq!(serde_yaml::from_str::<Vec<User>>(&yaml).unwrap(), users());
+    }
+}